@@ -0,0 +1,225 @@
+//! Conversion throughput for structs with 1, 2, and 10 versions.
+//!
+//! Run with `cargo bench --bench conversion`. Each group converts the oldest version (which the
+//! generated `from_version` matches last) and the latest version.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use serde::{Deserialize, Serialize};
+use serde_versioned::{FromVersion, Versioned};
+use std::hint::black_box;
+
+#[derive(Versioned, Clone)]
+#[versioned(versions = [OneV1], identity)]
+struct One {
+    name: String,
+    count: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct OneV1 {
+    name: String,
+    count: u32,
+}
+
+#[derive(Versioned, Clone)]
+#[versioned(versions = [TwoV1, TwoV2], identity)]
+struct Two {
+    name: String,
+    count: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct TwoV1 {
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct TwoV2 {
+    name: String,
+    count: u32,
+}
+
+impl FromVersion<Two> for TwoV1 {
+    fn convert(self) -> Two {
+        Two {
+            name: self.name,
+            count: 0,
+        }
+    }
+}
+
+// Ten versions: the first nine only have a name, and the latest adds the count
+macro_rules! name_only_versions {
+    ($($version:ident),*) => {
+        $(
+            #[derive(Serialize, Deserialize, Clone)]
+            struct $version {
+                name: String,
+            }
+
+            impl FromVersion<Ten> for $version {
+                fn convert(self) -> Ten {
+                    Ten {
+                        name: self.name,
+                        count: 0,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+name_only_versions!(
+    TenV1, TenV2, TenV3, TenV4, TenV5, TenV6, TenV7, TenV8, TenV9
+);
+
+#[derive(Versioned, Clone)]
+#[versioned(
+    versions = [TenV1, TenV2, TenV3, TenV4, TenV5, TenV6, TenV7, TenV8, TenV9, TenV10],
+    identity,
+)]
+struct Ten {
+    name: String,
+    count: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct TenV10 {
+    name: String,
+    count: u32,
+}
+
+// The same ten versions, reading only the latest
+#[derive(Versioned, Clone)]
+#[versioned(
+    versions = [TenV1, TenV2, TenV3, TenV4, TenV5, TenV6, TenV7, TenV8, TenV9, TenV10],
+    accept = "10..",
+)]
+struct Accepting {
+    name: String,
+    count: u32,
+}
+
+impl FromVersion<Accepting> for TenV10 {
+    fn convert(self) -> Accepting {
+        Accepting {
+            name: self.name,
+            count: self.count,
+        }
+    }
+}
+
+fn name() -> String {
+    "Alice".to_string()
+}
+
+fn bench_from_version(c: &mut Criterion) {
+    let mut group = c.benchmark_group("from_version");
+    group.bench_function(BenchmarkId::new("1 version", "latest"), |b| {
+        let version = OneVersion::Version1(OneV1 {
+            name: name(),
+            count: 1,
+        });
+        b.iter_batched(
+            || version.clone(),
+            |version| One::from_version(black_box(version)),
+            criterion::BatchSize::SmallInput,
+        );
+    });
+    for (label, version) in [
+        ("oldest", TwoVersion::Version1(TwoV1 { name: name() })),
+        (
+            "latest",
+            TwoVersion::Version2(TwoV2 {
+                name: name(),
+                count: 1,
+            }),
+        ),
+    ] {
+        group.bench_function(BenchmarkId::new("2 versions", label), |b| {
+            b.iter_batched(
+                || version.clone(),
+                |version| Two::from_version(black_box(version)),
+                criterion::BatchSize::SmallInput,
+            );
+        });
+    }
+    for (label, version) in [
+        ("oldest", TenVersion::Version1(TenV1 { name: name() })),
+        (
+            "latest",
+            TenVersion::Version10(TenV10 {
+                name: name(),
+                count: 1,
+            }),
+        ),
+    ] {
+        group.bench_function(BenchmarkId::new("10 versions", label), |b| {
+            b.iter_batched(
+                || version.clone(),
+                |version| Ten::from_version(black_box(version)),
+                criterion::BatchSize::SmallInput,
+            );
+        });
+    }
+    group.bench_function(BenchmarkId::new("10 versions with accept", "latest"), |b| {
+        let version = AcceptingVersion::Version10(TenV10 {
+            name: name(),
+            count: 1,
+        });
+        b.iter_batched(
+            || version.clone(),
+            |version| Accepting::from_version(black_box(version)),
+            criterion::BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
+
+fn bench_from_format(c: &mut Criterion) {
+    let mut group = c.benchmark_group("from_format");
+    let one = r#"{"version":"1","name":"Alice","count":1}"#;
+    group.bench_function(BenchmarkId::new("1 version", "latest"), |b| {
+        b.iter(|| One::from_format(black_box(one), serde_json::from_str));
+    });
+    let two = r#"{"version":"1","name":"Alice"}"#;
+    group.bench_function(BenchmarkId::new("2 versions", "oldest"), |b| {
+        b.iter(|| Two::from_format(black_box(two), serde_json::from_str));
+    });
+    let ten = r#"{"version":"1","name":"Alice"}"#;
+    group.bench_function(BenchmarkId::new("10 versions", "oldest"), |b| {
+        b.iter(|| Ten::from_format(black_box(ten), serde_json::from_str));
+    });
+    group.finish();
+}
+
+fn bench_to_version(c: &mut Criterion) {
+    let mut group = c.benchmark_group("to_version");
+    let ten = Ten {
+        name: name(),
+        count: 1,
+    };
+    group.bench_function("to_version", |b| {
+        b.iter(|| black_box(&ten).to_version());
+    });
+    group.bench_function("into_version", |b| {
+        b.iter_batched(
+            || ten.clone(),
+            |ten| black_box(ten).into_version(),
+            criterion::BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("extract_version_string", |b| {
+        let version = ten.to_version();
+        b.iter(|| Ten::extract_version_string(black_box(&version)));
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_from_version,
+    bench_from_format,
+    bench_to_version
+);
+criterion_main!(benches);
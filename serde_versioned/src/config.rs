@@ -0,0 +1,23 @@
+//! Process-wide settings shared by all versioned types.
+//!
+//! Settings are read on every conversion, so a change applies to conversions started afterwards
+//! on any thread.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+static CAPTURE_INPUT: AtomicBool = AtomicBool::new(true);
+
+/// Sets whether failed reads capture their input in `FormatError::Deserialize`.
+///
+/// Input is captured by default, which helps debugging. Disable it for deployments that must not
+/// keep the offending data in errors, for example because it may contain personal data. The
+/// length summary of binary input is kept either way.
+pub fn set_capture_input(capture: bool) {
+    CAPTURE_INPUT.store(capture, Ordering::Relaxed);
+}
+
+/// Returns whether failed reads capture their input, as set by [`set_capture_input`].
+#[must_use]
+pub fn capture_input() -> bool {
+    CAPTURE_INPUT.load(Ordering::Relaxed)
+}
@@ -0,0 +1,82 @@
+//! Types shared by the documentation examples, so that each example shows only the call it
+//! is about. This module is not part of the public API.
+
+use alloc::string::{String, ToString};
+
+use crate::{FromVersion, Versioned};
+use serde::{Deserialize, Serialize};
+
+/// The current struct of the examples.
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [UserV1, UserV2], downgrades = [UserV1], group = "accounts")]
+pub struct User {
+    pub name: String,
+    pub age: u32,
+}
+
+/// Version 1 of [`User`], before `age` was added.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserV1 {
+    pub name: String,
+}
+
+/// Version 2 of [`User`].
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserV2 {
+    pub name: String,
+    pub age: u32,
+}
+
+impl FromVersion<User> for UserV1 {
+    fn convert(self) -> User {
+        User {
+            name: self.name,
+            age: 0,
+        }
+    }
+}
+
+impl FromVersion<User> for UserV2 {
+    fn convert(self) -> User {
+        User {
+            name: self.name,
+            age: self.age,
+        }
+    }
+}
+
+impl crate::IntoVersion<User> for UserV1 {
+    fn downgrade(current: &User) -> Self {
+        UserV1 {
+            name: current.name.clone(),
+        }
+    }
+}
+
+/// A struct versioned together with [`User`].
+#[derive(Versioned, Serialize, Deserialize, Clone)]
+#[versioned(versions = [UserProfileV1], group = "accounts")]
+pub struct UserProfile {
+    pub bio: String,
+}
+
+/// Version 1 of [`UserProfile`].
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserProfileV1 {
+    pub bio: String,
+}
+
+impl FromVersion<UserProfile> for UserProfileV1 {
+    fn convert(self) -> UserProfile {
+        UserProfile { bio: self.bio }
+    }
+}
+
+/// Returns the user the examples write.
+#[must_use]
+pub fn user() -> User {
+    User {
+        name: "Alice".to_string(),
+        age: 30,
+    }
+}
@@ -0,0 +1,86 @@
+//! Detection of the text format of a serialized version.
+//!
+//! [`Format::sniff`] tells JSON from YAML so that a document can be upgraded and written back in
+//! the format it was read in, as done by
+//! [`Versioned::migrate_same_format`](crate::Versioned::migrate_same_format).
+//! [`AutoFormatError`] collects the errors of every format tried by
+//! [`Versioned::from_auto`](crate::Versioned::from_auto).
+
+use std::error::Error;
+use std::fmt;
+
+/// A text format a version can be read from and written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JSON, read and written with `serde_json`
+    Json,
+    /// YAML, read and written with `serde_yaml`
+    Yaml,
+}
+
+impl Format {
+    /// Detects the format of `input`.
+    ///
+    /// Every JSON document is also valid YAML, so input that parses as JSON is reported as
+    /// [`Format::Json`] and anything else as [`Format::Yaml`].
+    #[must_use]
+    pub fn sniff(input: &str) -> Self {
+        if serde_json::from_str::<serde::de::IgnoredAny>(input).is_ok() {
+            Self::Json
+        } else {
+            Self::Yaml
+        }
+    }
+}
+
+/// Error of the format detected by [`Format::sniff`].
+#[derive(Debug)]
+pub enum SniffedFormatError {
+    /// Error reading or writing JSON
+    Json(serde_json::Error),
+    /// Error reading or writing YAML
+    Yaml(serde_yaml::Error),
+}
+
+impl Error for SniffedFormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Json(e) => Some(e),
+            Self::Yaml(e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for SniffedFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "json: {e}"),
+            Self::Yaml(e) => write!(f, "yaml: {e}"),
+        }
+    }
+}
+
+/// Errors of every format tried by [`Versioned::from_auto`](crate::Versioned::from_auto), for
+/// input that none of them could read.
+#[cfg(feature = "toml")]
+#[derive(Debug)]
+pub struct AutoFormatError {
+    /// Error reading the input as JSON
+    pub json: serde_json::Error,
+    /// Error reading the input as TOML, boxed since TOML errors are large
+    pub toml: Box<toml::de::Error>,
+    /// Error reading the input as YAML
+    pub yaml: serde_yaml::Error,
+}
+
+#[cfg(feature = "toml")]
+impl Error for AutoFormatError {}
+
+#[cfg(feature = "toml")]
+impl fmt::Display for AutoFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // TOML errors span several lines with a snippet of the input
+        let toml = self.toml.message();
+        write!(f, "json: {}; toml: {toml}; yaml: {}", self.json, self.yaml)
+    }
+}
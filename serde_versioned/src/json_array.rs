@@ -0,0 +1,108 @@
+//! Streaming of the elements of a top-level JSON array.
+//!
+//! `serde_json` only streams whitespace-separated values, so the array punctuation is read here
+//! and each element is handed to `serde_json` on its own. Only the current element is held in
+//! memory, which keeps large exports from being loaded at once.
+
+use serde::de::{DeserializeOwned, Error as _};
+use std::io::{BufRead, BufReader, Read};
+use std::marker::PhantomData;
+
+/// Position of the reader within the array.
+enum State {
+    /// Before the opening `[`
+    Start,
+    /// After the opening `[`, before the first element
+    First,
+    /// After an element, before the next `,` or the closing `]`
+    Next,
+    /// After the closing `]` or a syntax error
+    Done,
+}
+
+/// Iterator over the elements of a JSON array read from `R`.
+///
+/// A syntax error ends the iteration after it is yielded, since the position of the next element
+/// is unknown. An element that does not match `T` is also fatal for the same reason.
+pub(crate) struct JsonArrayElements<R, T> {
+    reader: BufReader<R>,
+    state: State,
+    element: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T: DeserializeOwned> JsonArrayElements<R, T> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            state: State::Start,
+            element: PhantomData,
+        }
+    }
+
+    /// Skips whitespace and returns the next byte without consuming it.
+    fn peek(&mut self) -> Result<Option<u8>, serde_json::Error> {
+        loop {
+            let buf = self.reader.fill_buf().map_err(serde_json::Error::io)?;
+            if buf.is_empty() {
+                return Ok(None);
+            }
+            match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+                Some(offset) => {
+                    let byte = buf[offset];
+                    self.reader.consume(offset);
+                    return Ok(Some(byte));
+                }
+                None => {
+                    let len = buf.len();
+                    self.reader.consume(len);
+                }
+            }
+        }
+    }
+
+    /// Reads the next element, or `None` after the closing `]`.
+    fn next_element(&mut self) -> Result<Option<T>, serde_json::Error> {
+        if let State::Done = self.state {
+            return Ok(None);
+        }
+        if let State::Start = self.state {
+            if self.peek()? != Some(b'[') {
+                return Err(serde_json::Error::custom("expected a JSON array"));
+            }
+            self.reader.consume(1);
+            self.state = State::First;
+        }
+        let next = self.peek()?;
+        match (&self.state, next) {
+            (State::First | State::Next, Some(b']')) => {
+                self.reader.consume(1);
+                self.state = State::Done;
+                return Ok(None);
+            }
+            (State::Next, Some(b',')) => self.reader.consume(1),
+            (State::First, Some(_)) => {}
+            (_, None) => return Err(serde_json::Error::custom("unexpected end of JSON array")),
+            (_, Some(_)) => return Err(serde_json::Error::custom("expected `,` or `]`")),
+        }
+        // Objects and arrays end on their closing bracket, so the deserializer does not read
+        // past the element
+        let mut deserializer = serde_json::Deserializer::from_reader(&mut self.reader);
+        let element = T::deserialize(&mut deserializer)?;
+        self.state = State::Next;
+        Ok(Some(element))
+    }
+}
+
+impl<R: Read, T: DeserializeOwned> Iterator for JsonArrayElements<R, T> {
+    type Item = Result<T, serde_json::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_element() {
+            Ok(element) => element.map(Ok),
+            Err(e) => {
+                self.state = State::Done;
+                Some(Err(e))
+            }
+        }
+    }
+}
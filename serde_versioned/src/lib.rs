@@ -40,12 +40,60 @@
 //!     }
 //! }
 //! ```
+//!
+//! ## `no_std`
+//!
+//! Without the default `std` feature, the crate only needs `alloc`. The format shorthands and
+//! the integrations behind other features require `std` and enable it.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
-use serde::{Deserialize, Serialize};
-use std::error::Error;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+#[cfg(feature = "std")]
+use core::hash::Hash;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
 
 pub use serde_versioned_derive::Versioned;
 
+// Lets the derive's `serde_versioned::` paths resolve in the examples' fixture
+extern crate self as serde_versioned;
+
+#[doc(hidden)]
+#[path = "example.rs"]
+pub mod __example;
+
+pub mod config;
+pub mod probe;
+
+pub use probe::VersionProbe;
+
+mod unknown_version;
+
+#[cfg(all(feature = "json", feature = "yaml"))]
+pub mod format;
+
+#[cfg(all(feature = "json", feature = "yaml"))]
+pub use format::{Format, SniffedFormatError};
+
+#[cfg(all(feature = "json", feature = "yaml", feature = "toml"))]
+pub use format::AutoFormatError;
+
+#[cfg(feature = "json")]
+mod json_array;
+
+#[cfg(feature = "json")]
+pub mod versioned_value;
+
+#[cfg(feature = "json")]
+pub use versioned_value::VersionedValue;
+
 /// Trait for converting from a versioned struct to the current struct.
 ///
 /// This trait must be implemented for each version struct to define how it converts
@@ -78,6 +126,206 @@ pub trait FromVersion<T>: Sized {
     fn convert(self) -> T;
 }
 
+/// Trait for fallibly converting from a versioned struct to the current struct.
+///
+/// Implement this instead of [`FromVersion`] when a migration can fail, for example when a
+/// string field is parsed into an enum. Every [`FromVersion`] implementation is also a
+/// `TryFromVersion` implementation that never fails, and the derive macro converts each
+/// version through this trait, so the two can be mixed freely across versions.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use serde_versioned::{TryFromVersion, VersionConversionError};
+///
+/// enum Role { Admin, Member }
+/// struct User { name: String, role: Role }
+/// struct UserV1 { name: String, role: String }
+///
+/// impl TryFromVersion<User> for UserV1 {
+///     fn try_convert(self) -> Result<User, VersionConversionError> {
+///         let role = match self.role.as_str() {
+///             "admin" => Role::Admin,
+///             "member" => Role::Member,
+///             other => {
+///                 return Err(VersionConversionError::new(
+///                     "1",
+///                     format!("unknown role `{other}`").into(),
+///                 ));
+///             }
+///         };
+///         Ok(User { name: self.name, role })
+///     }
+/// }
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be converted to `{T}`",
+    label = "no conversion from `{Self}` to `{T}`",
+    note = "implement `FromVersion<{T}>` or `TryFromVersion<{T}>` for `{Self}`"
+)]
+pub trait TryFromVersion<T>: Sized {
+    /// Converts a versioned struct instance to the current struct type.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The versioned struct instance to convert
+    ///
+    /// # Returns
+    ///
+    /// The converted current struct instance
+    ///
+    /// # Errors
+    ///
+    /// Returns a `VersionConversionError` if the instance cannot be represented as the current struct.
+    fn try_convert(self) -> Result<T, VersionConversionError>;
+}
+
+impl<T, V: FromVersion<T>> TryFromVersion<T> for V {
+    fn try_convert(self) -> Result<T, VersionConversionError> {
+        Ok(self.convert())
+    }
+}
+
+/// Trait for converting the current struct back to an older version struct.
+///
+/// This is the reverse of [`FromVersion`], for writing data in an older format for consumers
+/// that cannot read the latest version yet. The conversion may be lossy, since older versions
+/// usually hold less information than the current struct.
+///
+/// Listing a version struct in the `downgrades` option of the `versioned` attribute makes
+/// [`Versioned::to_version_n`] use its implementation of this trait.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use serde_versioned::IntoVersion;
+///
+/// struct User { name: String, age: u32 }
+/// struct UserV1 { name: String }
+///
+/// impl IntoVersion<User> for UserV1 {
+///     fn downgrade(current: &User) -> Self {
+///         UserV1 { name: current.name.clone() }
+///     }
+/// }
+/// ```
+pub trait IntoVersion<T> {
+    /// Converts the current struct to this version struct.
+    ///
+    /// # Arguments
+    ///
+    /// * `current` - The current struct instance to convert
+    ///
+    /// # Returns
+    ///
+    /// The version struct instance
+    fn downgrade(current: &T) -> Self;
+}
+
+/// Trait for asynchronously converting from a versioned struct to the current struct.
+///
+/// This is the async counterpart of [`FromVersion`] for conversions that need to fetch
+/// data, such as calling a service to backfill a newly required field. It does not depend
+/// on any particular async runtime.
+///
+/// Adding `async` to the `versioned` attribute makes the derive macro generate
+/// `from_version_async`, which uses this trait for every version struct.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use serde_versioned::{AsyncFromVersion, VersionConversionError};
+///
+/// struct User { name: String, email: String }
+/// struct UserV1 { name: String }
+///
+/// async fn lookup_email(name: &str) -> String {
+///     format!("{name}@example.com")
+/// }
+///
+/// impl AsyncFromVersion<User> for UserV1 {
+///     async fn convert_async(self) -> Result<User, VersionConversionError> {
+///         let email = lookup_email(&self.name).await;
+///         Ok(User { name: self.name, email })
+///     }
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub trait AsyncFromVersion<T>: Sized {
+    /// Asynchronously converts a versioned struct instance to the current struct type.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The versioned struct instance to convert
+    ///
+    /// # Returns
+    ///
+    /// A future resolving to the converted current struct instance
+    ///
+    /// # Errors
+    ///
+    /// Returns a `VersionConversionError` if the data needed for the conversion cannot be obtained.
+    fn convert_async(self) -> impl Future<Output = Result<T, VersionConversionError>>;
+}
+
+/// Trait for converting from a versioned struct to the current struct with a caller-supplied
+/// context.
+///
+/// Some migrations need a value that is not stored in old versions and is not constant either,
+/// such as a default locale or a tenant id, to fill in fields added later. The context carries it
+/// into the conversion.
+///
+/// Adding `context = "MigrationContext"` to the `versioned` attribute makes the derive macro
+/// generate `from_version_with`, which uses this trait for every version struct.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use serde_versioned::FromVersionWith;
+///
+/// struct MigrationContext { locale: String }
+///
+/// struct User { name: String, locale: String }
+/// struct UserV1 { name: String }
+///
+/// impl FromVersionWith<User, MigrationContext> for UserV1 {
+///     fn convert_with(self, ctx: &MigrationContext) -> User {
+///         User { name: self.name, locale: ctx.locale.clone() }
+///     }
+/// }
+/// ```
+pub trait FromVersionWith<T, Ctx: ?Sized>: Sized {
+    /// Converts a versioned struct instance to the current struct type using `ctx`.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The versioned struct instance to convert
+    /// * `ctx` - The context supplied by the caller of `from_version_with`
+    ///
+    /// # Returns
+    ///
+    /// The converted current struct instance
+    fn convert_with(self, ctx: &Ctx) -> T;
+}
+
+/// Trait mapping a version struct to its version number within the versioned struct `T`.
+///
+/// The derive macro implements this trait for every version struct listed in the
+/// `versions` attribute.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use serde_versioned::{__example::*, Versioned};
+/// use serde_versioned::VersionMember;
+///
+/// assert_eq!(<UserV1 as VersionMember<User>>::VERSION, "1");
+/// ```
+pub trait VersionMember<T> {
+    /// The version number of this version struct (e.g., "1", "2").
+    const VERSION: &'static str;
+}
+
 /// Trait for handling versioned serialization and deserialization.
 ///
 /// This trait is automatically derived using the `#[derive(Versioned)]` macro.
@@ -89,19 +337,19 @@ pub trait FromVersion<T>: Sized {
 /// use serde_versioned::Versioned;
 /// use serde::{Deserialize, Serialize};
 ///
-/// #[derive(Versioned, Serialize, Deserialize)]
+/// #[derive(Versioned, Serialize, Deserialize, Clone)]
 /// #[versioned(versions = [UserV1, UserV2])]
 /// struct User {
 ///     name: String,
 ///     age: u32,
 /// }
 ///
-/// #[derive(Serialize, Deserialize)]
+/// #[derive(Serialize, Deserialize, Clone)]
 /// struct UserV1 {
 ///     name: String,
 /// }
 ///
-/// #[derive(Serialize, Deserialize)]
+/// #[derive(Serialize, Deserialize, Clone)]
 /// struct UserV2 {
 ///     name: String,
 ///     age: u32,
@@ -135,7 +383,28 @@ pub trait Versioned: Sized {
     ///
     /// This enum is automatically generated by the `#[derive(Versioned)]` macro
     /// and contains variants for each version specified in the `versions` attribute.
-    type VersionEnum: for<'a> Deserialize<'a> + Serialize;
+    ///
+    /// The generated enum implements `Serialize` and `Deserialize` unless the `derive_serde`
+    /// option restricts it to one of them. Methods that serialize or deserialize the enum
+    /// themselves require the corresponding trait.
+    type VersionEnum;
+
+    /// The key of the version tag in the serialized version enum.
+    ///
+    /// This is `"version"` unless the `tag` option of the `versioned` attribute sets another key.
+    const VERSION_TAG: &'static str = "version";
+
+    /// The key of the version's fields in the serialized version enum, or `None` when they sit
+    /// beside the version tag.
+    ///
+    /// This is set by the `repr = "adjacent"` and `content` options of the `versioned` attribute.
+    const VERSION_CONTENT: Option<&'static str> = None;
+
+    /// The group of structs whose versions move in lockstep with this one, if any.
+    ///
+    /// This is set by the `group` option of the `versioned` attribute, and checked by
+    /// [`Versioned::assert_same_version`].
+    const VERSION_GROUP: Option<&'static str> = None;
 
     /// Converts a versioned enum instance back to the current struct.
     ///
@@ -164,6 +433,59 @@ pub trait Versioned: Sized {
     /// The versioned enum instance representing this struct in its latest version.
     fn to_version(&self) -> Self::VersionEnum;
 
+    /// Converts the current struct instance into its versioned enum representation by value.
+    ///
+    /// This is the move-based counterpart of [`Versioned::to_version`]. The derive macro moves
+    /// each field into the latest version struct instead of cloning it, which avoids copying
+    /// large or borrowed (e.g. `Cow`) fields when the value is not needed afterwards.
+    ///
+    /// # Returns
+    ///
+    /// The versioned enum instance representing this struct in its latest version.
+    fn into_version(self) -> Self::VersionEnum {
+        self.to_version()
+    }
+
+    /// Converts the current struct instance to its versioned enum representation in the given
+    /// version.
+    ///
+    /// The latest version is always available. Older versions are available when their version
+    /// struct implements [`IntoVersion`] and is listed in the `downgrades` option of the
+    /// `versioned` attribute.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version number to write (e.g., "1")
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self::VersionEnum)` - The versioned enum instance in the requested version
+    /// * `Err(VersionConversionError)` - The struct cannot be written in the requested version
+    ///
+    /// # Errors
+    ///
+    /// Returns a `VersionConversionError` if the version is unknown or cannot be downgraded to.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # let user = user();
+    /// let v1 = user.to_version_n("1").unwrap();
+    /// let json = serde_json::to_string(&v1).unwrap();
+    /// ```
+    fn to_version_n(&self, version: &str) -> Result<Self::VersionEnum, VersionConversionError> {
+        let latest = self.to_version();
+        if Self::version_tag(&latest) == version {
+            Ok(latest)
+        } else {
+            Err(VersionConversionError::new(
+                version,
+                "cannot write the current struct in this version".into(),
+            ))
+        }
+    }
+
     /// Deserializes from a string format and converts to the current struct.
     ///
     /// This is a convenience method that combines deserialization and version conversion.
@@ -181,7 +503,8 @@ pub trait Versioned: Sized {
     /// # Errors
     ///
     /// Returns `FormatError::Deserialize` if deserialization fails, or
-    /// `FormatError::VersionConversion` if version conversion fails.
+    /// `FormatError::VersionConversion` if version conversion fails. The input is captured in
+    /// either error unless disabled with [`config::set_capture_input`].
     ///
     /// # Example
     ///
@@ -189,19 +512,19 @@ pub trait Versioned: Sized {
     /// use serde_versioned::Versioned;
     /// use serde::{Deserialize, Serialize};
     ///
-    /// #[derive(Versioned, Serialize, Deserialize)]
+    /// #[derive(Versioned, Serialize, Deserialize, Clone)]
     /// #[versioned(versions = [UserV1, UserV2])]
     /// struct User {
     ///     name: String,
     ///     age: u32,
     /// }
     ///
-    /// #[derive(Serialize, Deserialize)]
+    /// #[derive(Serialize, Deserialize, Clone)]
     /// struct UserV1 {
     ///     name: String,
     /// }
     ///
-    /// #[derive(Serialize, Deserialize)]
+    /// #[derive(Serialize, Deserialize, Clone)]
     /// struct UserV2 {
     ///     name: String,
     ///     age: u32,
@@ -228,96 +551,1589 @@ pub trait Versioned: Sized {
         E: Error + Send + Sync + 'static,
     {
         deserializer(input)
-            .map_err(|e| FormatError::deserialize(e, Some(input.to_string())))
-            .and_then(|version| Self::from_version(version).map_err(FormatError::VersionConversion))
+            .map_err(|e| {
+                FormatError::deserialize(e, config::capture_input().then(|| input.to_string()))
+            })
+            .and_then(|version| {
+                Self::from_version(version)
+                    .map_err(|e| FormatError::VersionConversion(e.with_captured_text(input)))
+            })
     }
 
-    /// Extracts version string from the version enum for error reporting.
+    /// Deserializes from a string format and converts to the current struct, also returning the
+    /// version the input was in.
     ///
-    /// This is a helper method that attempts to extract the version number
-    /// from the version enum for use in error messages.
+    /// This is [`Versioned::from_format`] for callers that record which versions arrive, such as
+    /// for counting documents still written in old versions, without a separate
+    /// [`Versioned::peek_version`] pass over the input.
     ///
     /// # Arguments
     ///
-    /// * `version` - The version enum instance
+    /// * `input` - The string to deserialize from
+    /// * `deserializer` - A function that deserializes the string into `Self::VersionEnum`
     ///
     /// # Returns
     ///
-    /// A string representation of the version number, or "unknown" if it cannot be determined.
-    #[doc(hidden)]
-    fn extract_version_string(_version: &Self::VersionEnum) -> String {
-        // This is a default implementation that returns "unknown".
-        // The derive macro should override this with a proper implementation
-        // that can extract the version from the enum.
-        "unknown".to_string()
+    /// * `Ok((Self, String))` - The converted struct and the version number of the input, as
+    ///   given by [`Versioned::extract_version_string`]
+    /// * `Err(FormatError<E>)` - Error during deserialization or conversion
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Versioned::from_format`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let (user, version) =
+    ///     User::from_format_with_version(r#"{"version":"1","name":"Alice"}"#, serde_json::from_str)
+    ///         .unwrap();
+    /// assert_eq!(version, "1");
+    /// ```
+    fn from_format_with_version<'a, F, E>(
+        input: &'a str,
+        deserializer: F,
+    ) -> Result<(Self, String), FormatError<E>>
+    where
+        F: FnOnce(&'a str) -> Result<Self::VersionEnum, E>,
+        E: Error + Send + Sync + 'static,
+    {
+        let version = deserializer(input).map_err(|e| {
+            FormatError::deserialize(e, config::capture_input().then(|| input.to_string()))
+        })?;
+        let version_string = Self::extract_version_string(&version);
+        Self::from_version(version)
+            .map(|current| (current, version_string))
+            .map_err(|e| FormatError::VersionConversion(e.with_captured_text(input)))
     }
 
-    /// Serializes the current struct to a string format via its versioned enum.
+    /// Deserializes from a string format whose version structs borrow from the input, and
+    /// converts to the current struct.
     ///
-    /// This is a convenience method that converts the struct to its versioned enum
-    /// and then serializes it using the provided serializer function.
+    /// This is [`Versioned::from_format`] for version enums that implement `Deserialize<'de>`
+    /// for the input's lifetime but not `DeserializeOwned`, such as those of version structs
+    /// with `&'static str` fields read from input embedded in the binary. The fields then point
+    /// into the input instead of holding copies of it.
     ///
     /// # Arguments
     ///
-    /// * `serializer` - A function that serializes `Self::VersionEnum` to the desired format
+    /// * `input` - The string to deserialize from, which the result may borrow from
+    /// * `deserializer` - A function that deserializes the string into `Self::VersionEnum`
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Versioned::from_format`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let user =
+    ///     User::from_format_borrowed(r#"{"version":"1","name":"Alice"}"#, serde_json::from_str)
+    ///         .unwrap();
+    /// ```
+    fn from_format_borrowed<'de, F, E>(
+        input: &'de str,
+        deserializer: F,
+    ) -> Result<Self, FormatError<E>>
+    where
+        F: FnOnce(&'de str) -> Result<Self::VersionEnum, E>,
+        Self::VersionEnum: serde::Deserialize<'de>,
+        E: Error + Send + Sync + 'static,
+    {
+        Self::from_format(input, deserializer)
+    }
+
+    /// Deserializes from a reader and converts to the current struct.
+    ///
+    /// This is the counterpart of [`Versioned::from_format`] for files and sockets, which
+    /// avoids reading the whole input into memory first.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader to deserialize from
+    /// * `deserializer` - A function that deserializes the reader into `Self::VersionEnum`,
+    ///   such as `serde_json::from_reader`
     ///
     /// # Returns
     ///
-    /// * `Ok(T)` - Successfully serialized data
-    /// * `Err(E)` - Error during serialization
+    /// * `Ok(Self)` - Successfully deserialized and converted struct
+    /// * `Err(FormatError<E>)` - Error during deserialization or conversion
     ///
     /// # Errors
     ///
-    /// Returns an error if serialization fails.
+    /// Returns `FormatError::Deserialize` if deserialization fails, or
+    /// `FormatError::VersionConversion` if version conversion fails. The input is not captured,
+    /// since it cannot be read again.
     ///
     /// # Example
     ///
     /// ```rust,no_run
-    /// use serde_versioned::Versioned;
-    /// use serde::{Deserialize, Serialize};
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let file = std::fs::File::open("user.json").unwrap();
+    /// let user = User::from_reader(std::io::BufReader::new(file), serde_json::from_reader).unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    fn from_reader<R, F, E>(reader: R, deserializer: F) -> Result<Self, FormatError<E>>
+    where
+        R: std::io::Read,
+        F: FnOnce(R) -> Result<Self::VersionEnum, E>,
+        E: Error + Send + Sync + 'static,
+    {
+        deserializer(reader)
+            .map_err(|e| FormatError::deserialize(e, None))
+            .and_then(|version| Self::from_version(version).map_err(FormatError::VersionConversion))
+    }
+
+    /// Deserializes from a binary format and converts to the current struct.
     ///
-    /// #[derive(Versioned, Serialize, Deserialize)]
-    /// #[versioned(versions = [UserV1, UserV2])]
-    /// struct User {
-    ///     name: String,
-    ///     age: u32,
-    /// }
+    /// This is the counterpart of [`Versioned::from_format`] for formats that work on bytes,
+    /// such as MessagePack or CBOR.
     ///
-    /// #[derive(Serialize, Deserialize)]
-    /// struct UserV1 {
-    ///     name: String,
-    /// }
+    /// # Arguments
     ///
-    /// #[derive(Serialize, Deserialize)]
-    /// struct UserV2 {
-    ///     name: String,
-    ///     age: u32,
-    /// }
+    /// * `bytes` - The bytes to deserialize from
+    /// * `deserializer` - A function that deserializes the bytes into `Self::VersionEnum`,
+    ///   such as `rmp_serde::from_slice`
     ///
-    /// impl serde_versioned::FromVersion<User> for UserV1 {
-    ///     fn convert(self) -> User {
-    ///         User { name: self.name, age: 0 }
-    ///     }
-    /// }
+    /// # Returns
     ///
-    /// impl serde_versioned::FromVersion<User> for UserV2 {
-    ///     fn convert(self) -> User {
-    ///         User { name: self.name, age: self.age }
-    ///     }
-    /// }
+    /// * `Ok(Self)` - Successfully deserialized and converted struct
+    /// * `Err(FormatError<E>)` - Error during deserialization or conversion
     ///
-    /// let user = User { name: "Alice".to_string(), age: 30 };
-    /// let json = user.to_format(serde_json::to_string).unwrap();
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if deserialization fails, or
+    /// `FormatError::VersionConversion` if version conversion fails. The length of the input is
+    /// captured as [`FormatInput::Bytes`] instead of its contents.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # let user = user();
+    /// let bytes = user.to_bytes(rmp_serde::to_vec).unwrap();
+    /// let user = User::from_bytes(&bytes, rmp_serde::from_slice).unwrap();
     /// ```
-    fn to_format<F, T, E>(&self, serializer: F) -> Result<T, E>
+    fn from_bytes<'a, F, E>(bytes: &'a [u8], deserializer: F) -> Result<Self, FormatError<E>>
     where
-        F: FnOnce(&Self::VersionEnum) -> Result<T, E>,
+        F: FnOnce(&'a [u8]) -> Result<Self::VersionEnum, E>,
+        E: Error + Send + Sync + 'static,
+    {
+        deserializer(bytes)
+            .map_err(|e| FormatError::deserialize_bytes(e, bytes))
+            .and_then(|version| {
+                Self::from_version(version).map_err(|e| {
+                    FormatError::VersionConversion(
+                        e.with_input(FormatInput::Bytes { len: bytes.len() }),
+                    )
+                })
+            })
+    }
+
+    /// Reads the version tag of a serialized payload without deserializing the rest of it.
+    ///
+    /// The payload is deserialized into a [`VersionProbe`], which skips every field that cannot
+    /// be a version tag, so this is cheaper than [`Versioned::from_format`] and does not fail on
+    /// malformed fields. The tag is read from the [`Versioned::VERSION_TAG`] field. This is useful
+    /// for routing documents by version.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The string to read the version tag from
+    /// * `parse` - A function that deserializes the string into a [`VersionProbe`]
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The version tag (e.g., "1", "2")
+    /// * `Err(FormatError<E>)` - Error during deserialization
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if the input is malformed or has no version tag.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let version = User::peek_version(r#"{"version":"1","name":"Alice"}"#, serde_json::from_str).unwrap();
+    /// assert_eq!(version, "1");
+    /// ```
+    fn peek_version<'a, F, E>(input: &'a str, parse: F) -> Result<String, FormatError<E>>
+    where
+        F: FnOnce(&'a str) -> Result<VersionProbe, E>,
+        E: Error + serde::de::Error + Send + Sync + 'static,
+    {
+        let probe = parse(input).map_err(|e| {
+            FormatError::deserialize(e, config::capture_input().then(|| input.to_string()))
+        })?;
+        probe
+            .tag(Self::VERSION_TAG)
+            .map(str::to_string)
+            .ok_or_else(|| {
+                FormatError::deserialize(
+                    E::missing_field(Self::VERSION_TAG),
+                    config::capture_input().then(|| input.to_string()),
+                )
+            })
+    }
+
+    /// Deserializes an old-format and a new-format payload and checks that both convert to the
+    /// same current struct.
+    ///
+    /// This is meant for dual-write migrations, where a record is written in both the old and
+    /// the new version during the transition, to catch writers whose two payloads diverge.
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - The payload written in the old version
+    /// * `new` - The payload written in the new version
+    /// * `deserializer` - A function that deserializes a string into `Self::VersionEnum`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Both payloads convert to equal current structs
+    /// * `Err(FormatError<E>)` - Error during deserialization or conversion, or a mismatch
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if either payload cannot be deserialized, or
+    /// `FormatError::VersionConversion` if either conversion fails or the converted structs differ.
+    /// A mismatch is reported with the version of the new payload and a message showing both
+    /// converted values.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// User::verify_wire_compatibility(
+    ///     r#"{"version":"1","name":"Alice"}"#,
+    ///     r#"{"version":"2","name":"Alice","age":0}"#,
+    ///     serde_json::from_str,
+    /// )
+    /// .unwrap();
+    /// ```
+    fn verify_wire_compatibility<'a, F, E>(
+        old: &'a str,
+        new: &'a str,
+        deserializer: F,
+    ) -> Result<(), FormatError<E>>
+    where
+        F: Fn(&'a str) -> Result<Self::VersionEnum, E>,
+        E: Error + Send + Sync + 'static,
+        Self: PartialEq + core::fmt::Debug,
+    {
+        let old_version = deserializer(old).map_err(|e| {
+            FormatError::deserialize(e, config::capture_input().then(|| old.to_string()))
+        })?;
+        let new_version = deserializer(new).map_err(|e| {
+            FormatError::deserialize(e, config::capture_input().then(|| new.to_string()))
+        })?;
+        let old_version_string = Self::extract_version_string(&old_version);
+        let new_version_string = Self::extract_version_string(&new_version);
+
+        let old_current =
+            Self::from_version(old_version).map_err(FormatError::VersionConversion)?;
+        let new_current =
+            Self::from_version(new_version).map_err(FormatError::VersionConversion)?;
+        if old_current == new_current {
+            Ok(())
+        } else {
+            Err(FormatError::VersionConversion(
+                VersionConversionError::with_context(
+                    new_version_string.clone(),
+                    format!(
+                        "version {old_version_string} converts to {old_current:?}, but version {new_version_string} converts to {new_current:?}"
+                    )
+                    .into(),
+                    "old and new payloads diverge",
+                ),
+            ))
+        }
+    }
+
+    /// Deserializes JSON bytes and converts to the current struct.
+    ///
+    /// This is the common case of reading JSON from a file, socket, or database column
+    /// without first converting it to a string. Bytes that are valid UTF-8 are read as in
+    /// [`Versioned::from_json`], which captures them as text. Invalid UTF-8 is reported as a
+    /// deserialization error naming the invalid bytes, and only the length of the input is
+    /// captured (`FormatInput::Bytes`), since it cannot be shown as text.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The JSON bytes to deserialize from
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self)` - Successfully deserialized and converted struct
+    /// * `Err(FormatError<serde_json::Error>)` - Error during deserialization or conversion
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if the bytes are not valid JSON for the version enum, or
+    /// `FormatError::VersionConversion` if version conversion fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let user = User::from_json_bytes(br#"{"version":"1","name":"Alice"}"#).unwrap();
+    /// ```
+    #[cfg(feature = "json")]
+    fn from_json_bytes(bytes: &[u8]) -> Result<Self, FormatError<serde_json::Error>>
+    where
+        Self::VersionEnum: serde::de::DeserializeOwned,
+    {
+        match core::str::from_utf8(bytes) {
+            Ok(input) => Self::from_json(input),
+            Err(e) => Err(FormatError::deserialize_bytes(
+                serde::de::Error::custom(format_args!("invalid UTF-8: {e}")),
+                bytes,
+            )),
+        }
+    }
+
+    /// Deserializes JSON and converts to the current struct.
+    ///
+    /// Shorthand for [`Versioned::from_format`] with `serde_json::from_str`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if the input is not valid JSON for the version enum, or
+    /// `FormatError::VersionConversion` if version conversion fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let user = User::from_json(r#"{"version":"1","name":"Alice"}"#).unwrap();
+    /// ```
+    #[cfg(feature = "json")]
+    fn from_json(input: &str) -> Result<Self, FormatError<serde_json::Error>>
+    where
+        Self::VersionEnum: serde::de::DeserializeOwned,
+    {
+        Self::from_format(input, serde_json::from_str)
+    }
+
+    /// Deserializes YAML and converts to the current struct.
+    ///
+    /// Shorthand for [`Versioned::from_format`] with `serde_yaml::from_str`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if the input is not valid YAML for the version enum, or
+    /// `FormatError::VersionConversion` if version conversion fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let user = User::from_yaml("version: '1'\nname: Alice\n").unwrap();
+    /// ```
+    #[cfg(feature = "yaml")]
+    fn from_yaml(input: &str) -> Result<Self, FormatError<serde_yaml::Error>>
+    where
+        Self::VersionEnum: serde::de::DeserializeOwned,
+    {
+        Self::from_format(input, serde_yaml::from_str)
+    }
+
+    /// Deserializes TOML and converts to the current struct.
+    ///
+    /// Shorthand for [`Versioned::from_format`] with `toml::from_str`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if the input is not valid TOML for the version enum, or
+    /// `FormatError::VersionConversion` if version conversion fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let user = User::from_toml("version = \"1\"\nname = \"Alice\"\n").unwrap();
+    /// ```
+    #[cfg(feature = "toml")]
+    fn from_toml(input: &str) -> Result<Self, FormatError<toml::de::Error>>
+    where
+        Self::VersionEnum: serde::de::DeserializeOwned,
+    {
+        Self::from_format(input, toml::from_str)
+    }
+
+    /// Deserializes MessagePack and converts to the current struct.
+    ///
+    /// Shorthand for [`Versioned::from_bytes`] with `rmp_serde::from_slice`, so the length of the
+    /// input is captured instead of its contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if the bytes are not valid MessagePack for the version
+    /// enum, or `FormatError::VersionConversion` if version conversion fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # let user = user();
+    /// let user = User::from_binary(&user.to_binary().unwrap()).unwrap();
+    /// ```
+    #[cfg(feature = "binary")]
+    fn from_binary(bytes: &[u8]) -> Result<Self, FormatError<rmp_serde::decode::Error>>
+    where
+        Self::VersionEnum: serde::de::DeserializeOwned,
+    {
+        Self::from_bytes(bytes, rmp_serde::from_slice)
+    }
+
+    /// Deserializes from a string format, archives the incoming version as JSON, and converts
+    /// to the current struct.
+    ///
+    /// The returned `serde_json::Value` is the version as it was read (before conversion),
+    /// including its version tag, so it can be stored for auditing without parsing the input again.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The string to deserialize from
+    /// * `deserializer` - A function that deserializes the string into `Self::VersionEnum`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((serde_json::Value, Self))` - The archived incoming version and the converted struct
+    /// * `Err(FormatError<E>)` - Error during deserialization, archiving, or conversion
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if deserialization fails, or
+    /// `FormatError::VersionConversion` if the version cannot be archived as JSON or converted.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let json = r#"{"version":"1","name":"Alice"}"#;
+    /// let (archived, user) = User::archive_and_convert(json, serde_json::from_str).unwrap();
+    /// assert_eq!(archived["version"], "1");
+    /// ```
+    #[cfg(feature = "json")]
+    fn archive_and_convert<'a, F, E>(
+        input: &'a str,
+        deserializer: F,
+    ) -> Result<(serde_json::Value, Self), FormatError<E>>
+    where
+        F: FnOnce(&'a str) -> Result<Self::VersionEnum, E>,
+        E: Error + Send + Sync + 'static,
+        Self::VersionEnum: serde::Serialize,
+    {
+        let version = deserializer(input).map_err(|e| {
+            FormatError::deserialize(e, config::capture_input().then(|| input.to_string()))
+        })?;
+        let archived = serde_json::to_value(&version).map_err(|e| {
+            FormatError::VersionConversion(VersionConversionError::with_context(
+                Self::extract_version_string(&version),
+                Box::new(e),
+                "failed to archive the incoming version as JSON",
+            ))
+        })?;
+        let current = Self::from_version(version).map_err(FormatError::VersionConversion)?;
+        Ok((archived, current))
+    }
+
+    /// Upgrades a serialized value to the latest version in the same format.
+    ///
+    /// The input is deserialized in whatever version it was written, converted to the current
+    /// struct, and serialized again as the latest version. Input that is already the latest
+    /// version goes through the same conversion and round-trips unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The string to upgrade
+    /// * `deserializer` - A function that deserializes the string into `Self::VersionEnum`
+    /// * `serializer` - A function that serializes `Self::VersionEnum` into a string
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The upgraded output
+    /// * `Err(FormatError<E>)` - Error during deserialization, conversion, or serialization
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if deserialization fails, `FormatError::VersionConversion`
+    /// if conversion fails, or `FormatError::Serialize` if serialization fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let output = User::migrate(
+    ///     r#"{"version":"1","name":"Alice"}"#,
+    ///     serde_json::from_str,
+    ///     serde_json::to_string,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(output, r#"{"version":"2","name":"Alice","age":0}"#);
+    /// ```
+    fn migrate<'a, D, S, E>(
+        input: &'a str,
+        deserializer: D,
+        serializer: S,
+    ) -> Result<String, FormatError<E>>
+    where
+        D: FnOnce(&'a str) -> Result<Self::VersionEnum, E>,
+        S: FnOnce(&Self::VersionEnum) -> Result<String, E>,
+        E: Error + Send + Sync + 'static,
+    {
+        let current = Self::from_format(input, deserializer)?;
+        serializer(&current.into_version()).map_err(FormatError::serialize)
+    }
+
+    /// Rewrites a serialized value stored in one version as another, newer version.
+    ///
+    /// This is for backfills that move stored data between two versions without ending at the
+    /// latest one. The input is converted to the current struct and written with
+    /// [`Versioned::to_version_n`], so `to` must be the latest version or listed in `downgrades`.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The string to upgrade
+    /// * `from` - The version number the input must be in (e.g., "1")
+    /// * `to` - The version number to write (e.g., "2")
+    /// * `deserializer` - A function that deserializes the string into `Self::VersionEnum`
+    /// * `serializer` - A function that serializes `Self::VersionEnum` into a string
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::VersionConversion` if `from` or `to` is not a declared version, if
+    /// `to` is older than `from`, if the input is in another version than `from`, or if the
+    /// conversion fails or `to` cannot be written. Deserialization and serialization errors are
+    /// returned as by [`Versioned::migrate`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let output = User::upgrade_between(
+    ///     r#"{"version":"1","name":"Alice"}"#,
+    ///     "1",
+    ///     "2",
+    ///     serde_json::from_str,
+    ///     serde_json::to_string,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(output, r#"{"version":"2","name":"Alice","age":0}"#);
+    /// ```
+    fn upgrade_between<'a, D, S, E>(
+        input: &'a str,
+        from: &str,
+        to: &str,
+        deserializer: D,
+        serializer: S,
+    ) -> Result<String, FormatError<E>>
+    where
+        D: FnOnce(&'a str) -> Result<Self::VersionEnum, E>,
+        S: FnOnce(&Self::VersionEnum) -> Result<String, E>,
+        E: Error + Send + Sync + 'static,
+    {
+        for version in [from, to] {
+            if !Self::all_versions().contains(&version) {
+                return Err(FormatError::version_conversion(
+                    version,
+                    "version is not declared".into(),
+                ));
+            }
+        }
+        if Self::compare_versions(from, to) == Some(core::cmp::Ordering::Greater) {
+            return Err(FormatError::version_conversion(
+                from,
+                format!("cannot upgrade to the older version {to}").into(),
+            ));
+        }
+        let version = deserializer(input).map_err(|e| {
+            FormatError::deserialize(e, config::capture_input().then(|| input.to_string()))
+        })?;
+        // Check the version before converting, so input in another version is never converted
+        let version_tag = Self::version_tag(&version);
+        if version_tag != from {
+            return Err(FormatError::version_conversion(
+                version_tag,
+                format!("expected the input in version {from}").into(),
+            ));
+        }
+        let current = Self::from_version(version)
+            .map_err(|e| FormatError::VersionConversion(e.with_captured_text(input)))?;
+        let output = current
+            .to_version_n(to)
+            .map_err(FormatError::VersionConversion)?;
+        serializer(&output).map_err(FormatError::serialize)
+    }
+
+    /// Upgrades a serialized value to the latest version and reports which fields changed.
+    ///
+    /// The input is deserialized, converted to the current struct, and serialized again in the
+    /// latest version. The incoming and outgoing versions are compared field by field through
+    /// their JSON representation, so the report can be written to a change-audit log.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The string to upgrade
+    /// * `deserializer` - A function that deserializes the string into `Self::VersionEnum`
+    /// * `serializer` - A function that serializes `Self::VersionEnum` into a string
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((String, Vec<FieldChange>))` - The upgraded output and the changed fields, sorted by name
+    /// * `Err(FormatError<E>)` - Error during deserialization, conversion, or serialization
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if deserialization fails, `FormatError::VersionConversion`
+    /// if conversion fails or a version cannot be represented as JSON, or `FormatError::Serialize`
+    /// if serialization fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let (output, changes) = User::migrate_with_report(
+    ///     r#"{"version":"1","name":"Alice"}"#,
+    ///     serde_json::from_str,
+    ///     serde_json::to_string,
+    /// )
+    /// .unwrap();
+    /// assert!(changes.iter().any(|change| change.field == "age"));
+    /// ```
+    #[cfg(feature = "json")]
+    fn migrate_with_report<'a, D, S, E>(
+        input: &'a str,
+        deserializer: D,
+        serializer: S,
+    ) -> Result<(String, Vec<FieldChange>), FormatError<E>>
+    where
+        D: FnOnce(&'a str) -> Result<Self::VersionEnum, E>,
+        S: FnOnce(&Self::VersionEnum) -> Result<String, E>,
+        E: Error + Send + Sync + 'static,
+        Self::VersionEnum: serde::Serialize,
+    {
+        let to_json = |version: &Self::VersionEnum| {
+            serde_json::to_value(version).map_err(|e| {
+                FormatError::VersionConversion(VersionConversionError::with_context(
+                    Self::extract_version_string(version),
+                    Box::new(e),
+                    "failed to compare versions as JSON",
+                ))
+            })
+        };
+
+        let old_version = deserializer(input).map_err(|e| {
+            FormatError::deserialize(e, config::capture_input().then(|| input.to_string()))
+        })?;
+        let old = to_json(&old_version)?;
+        let new_version = Self::from_version(old_version)
+            .map_err(FormatError::VersionConversion)?
+            .into_version();
+        let new = to_json(&new_version)?;
+        let output = serializer(&new_version).map_err(FormatError::serialize)?;
+
+        Ok((output, FieldChange::diff(&old, &new)))
+    }
+
+    /// Deserializes input whose format is not known up front and converts to the current struct.
+    ///
+    /// The input is read as JSON, then TOML, then YAML, and the first format that reads it is
+    /// used. JSON goes first since every JSON document is also YAML. A conversion error is
+    /// returned as soon as it occurs, without trying the remaining formats.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The JSON, TOML, or YAML string to deserialize from
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self)` - Successfully deserialized and converted struct
+    /// * `Err(FormatError<AutoFormatError>)` - Error during deserialization or conversion
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` with the error of every format if none can read the
+    /// input, or `FormatError::VersionConversion` if version conversion fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let user = User::from_auto("version = \"1\"\nname = \"Alice\"\n").unwrap();
+    /// ```
+    #[cfg(all(feature = "json", feature = "yaml", feature = "toml"))]
+    fn from_auto(input: &str) -> Result<Self, FormatError<AutoFormatError>>
+    where
+        Self::VersionEnum: serde::de::DeserializeOwned,
+    {
+        let convert = |version| {
+            Self::from_version(version)
+                .map_err(|e| FormatError::VersionConversion(e.with_captured_text(input)))
+        };
+        let json = match serde_json::from_str(input) {
+            Ok(version) => return convert(version),
+            Err(e) => e,
+        };
+        let toml = match toml::from_str(input) {
+            Ok(version) => return convert(version),
+            Err(e) => e,
+        };
+        let yaml = match serde_yaml::from_str(input) {
+            Ok(version) => return convert(version),
+            Err(e) => e,
+        };
+        Err(FormatError::deserialize(
+            AutoFormatError {
+                json,
+                toml: Box::new(toml),
+                yaml,
+            },
+            config::capture_input().then(|| input.to_string()),
+        ))
+    }
+
+    /// Upgrades a serialized value to the latest version, keeping the format it was written in.
+    ///
+    /// The format is detected with [`Format::sniff`], so JSON input produces JSON output and
+    /// YAML input produces YAML output without the caller choosing a format.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The JSON or YAML string to upgrade
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The upgraded output in the format of the input
+    /// * `Err(FormatError<SniffedFormatError>)` - Error during deserialization, conversion, or
+    ///   serialization
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if deserialization fails, `FormatError::VersionConversion`
+    /// if conversion fails, or `FormatError::Serialize` if serialization fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let output = User::migrate_same_format("version: '1'\nname: Alice\n").unwrap();
+    /// assert_eq!(output, "version: '2'\nname: Alice\nage: 0\n");
+    /// ```
+    #[cfg(all(feature = "json", feature = "yaml"))]
+    fn migrate_same_format(input: &str) -> Result<String, FormatError<SniffedFormatError>>
+    where
+        Self::VersionEnum: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        match Format::sniff(input) {
+            Format::Json => Self::migrate(input, serde_json::from_str, serde_json::to_string)
+                .map_err(|e| e.map_deserialize(SniffedFormatError::Json)),
+            Format::Yaml => Self::migrate(input, serde_yaml::from_str, serde_yaml::to_string)
+                .map_err(|e| e.map_deserialize(SniffedFormatError::Yaml)),
+        }
+    }
+
+    /// Upgrades each JSON message of a stream to the current struct.
+    ///
+    /// This is the async counterpart of calling [`Versioned::from_format`] on each message,
+    /// for pipelines such as message queue consumers that receive mixed-version records.
+    /// Each item is converted independently, so one malformed message does not end the stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - A stream of JSON messages
+    ///
+    /// # Returns
+    ///
+    /// A stream yielding the converted struct or the `FormatError` for each message.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use tokio_stream::StreamExt;
+    ///
+    /// let messages = tokio_stream::iter(vec![r#"{"version":"1","name":"Alice"}"#.to_string()]);
+    /// let mut users = User::migrate_stream(messages);
+    /// while let Some(user) = users.next().await {
+    ///     println!("{}", user?.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "stream")]
+    fn migrate_stream<S>(
+        stream: S,
+    ) -> impl tokio_stream::Stream<Item = Result<Self, FormatError<serde_json::Error>>>
+    where
+        S: tokio_stream::Stream<Item = String>,
+        Self::VersionEnum: serde::de::DeserializeOwned,
+    {
+        tokio_stream::StreamExt::map(stream, |message| {
+            Self::from_format(&message, serde_json::from_str)
+        })
+    }
+
+    /// Reads a top-level JSON array of version enums and converts each element to the current
+    /// struct as it is read, without loading the whole array into memory.
+    ///
+    /// A failing conversion is yielded and the iteration continues with the next element. Malformed
+    /// JSON is yielded as `FormatError::Deserialize` and ends the iteration, since the next element
+    /// cannot be located.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader holding the JSON array
+    ///
+    /// # Returns
+    ///
+    /// An iterator yielding the converted struct or the `FormatError` for each element.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let file = std::fs::File::open("users.json")?;
+    /// for user in User::from_json_array_reader(file) {
+    ///     println!("{}", user?.name);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "json")]
+    fn from_json_array_reader<R: std::io::Read>(
+        reader: R,
+    ) -> impl Iterator<Item = Result<Self, FormatError<serde_json::Error>>>
+    where
+        Self::VersionEnum: serde::de::DeserializeOwned,
+    {
+        json_array::JsonArrayElements::new(reader).map(|element| {
+            element
+                .map_err(|e| FormatError::deserialize(e, None))
+                .and_then(|version| {
+                    Self::from_version(version).map_err(FormatError::VersionConversion)
+                })
+        })
+    }
+
+    /// Converts every value of a map of version enums to the current struct, keeping the keys.
+    ///
+    /// Each value is converted independently, so a failing value does not prevent the others
+    /// from being converted. The result for each key is either the converted struct or the
+    /// `VersionConversionError` for that value.
+    ///
+    /// # Arguments
+    ///
+    /// * `map` - The map whose values should be converted
+    ///
+    /// # Returns
+    ///
+    /// A map with the same keys holding the conversion result of each value.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use std::collections::HashMap;
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let mut stored = HashMap::new();
+    /// stored.insert("alice", serde_json::from_str(r#"{"version":"1","name":"Alice"}"#)?);
+    ///
+    /// let users = User::convert_map_values(stored);
+    /// assert_eq!(users["alice"].as_ref().unwrap().name, "Alice");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "std")]
+    fn convert_map_values<K>(
+        map: HashMap<K, Self::VersionEnum>,
+    ) -> HashMap<K, Result<Self, VersionConversionError>>
+    where
+        K: Eq + Hash,
+    {
+        map.into_iter()
+            .map(|(key, version)| (key, Self::from_version(version)))
+            .collect()
+    }
+
+    /// Deserializes and converts each of several inputs, as [`Versioned::from_format`] does.
+    ///
+    /// Each input is handled independently, so a malformed or unconvertible input does not
+    /// prevent the others from being converted. The results are in the order of the inputs, so a
+    /// failure can be traced back to its record.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - The strings to deserialize from
+    /// * `deserializer` - A function that deserializes a string into `Self::VersionEnum`, such as
+    ///   `serde_json::from_str`
+    ///
+    /// # Returns
+    ///
+    /// The converted struct or the `FormatError` for each input.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let records = [r#"{"version":"1","name":"Alice"}"#, "not json"];
+    /// let users = User::from_format_all(records, serde_json::from_str);
+    /// assert!(users[0].is_ok());
+    /// assert!(users[1].is_err());
+    /// ```
+    fn from_format_all<'a, I, F, E>(
+        inputs: I,
+        mut deserializer: F,
+    ) -> Vec<Result<Self, FormatError<E>>>
+    where
+        I: IntoIterator<Item = &'a str>,
+        F: FnMut(&'a str) -> Result<Self::VersionEnum, E>,
+        E: Error + Send + Sync + 'static,
+    {
+        inputs
+            .into_iter()
+            .map(|input| Self::from_format(input, &mut deserializer))
+            .collect()
+    }
+
+    /// Extracts version string from the version enum for error reporting.
+    ///
+    /// This is a helper method that attempts to extract the version number
+    /// from the version enum for use in error messages.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version enum instance
+    ///
+    /// # Returns
+    ///
+    /// A string representation of the version number, or "unknown" if it cannot be determined.
+    #[doc(hidden)]
+    fn extract_version_string(_version: &Self::VersionEnum) -> String {
+        // This is a default implementation that returns "unknown".
+        // The derive macro should override this with a proper implementation
+        // that can extract the version from the enum.
+        "unknown".to_string()
+    }
+
+    /// Returns the version number of a version enum instance, reading it from the serialized
+    /// value when the variant does not determine it.
+    ///
+    /// [`Versioned::extract_version_string`] can only map each variant to a fixed version.
+    /// Hand-written version enums may hold payloads of other versions, such as an
+    /// `Unknown(serde_json::Value)` variant, whose version must be read from the data and may be
+    /// missing. For variants for which [`Versioned::known_version_tag`] returns `None`, this
+    /// serializes the instance and reads its [`Versioned::VERSION_TAG`] field, which may hold a
+    /// string or an integer.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version enum instance
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The version number (e.g., "1", "2")
+    /// * `Err(FormatError<serde_json::Error>)` - The version could not be determined
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Serialize` if the instance cannot be serialized as JSON, or
+    /// `FormatError::Deserialize` if the serialized value has no string or integer version tag.
+    #[cfg(feature = "json")]
+    fn try_extract_version(
+        version: &Self::VersionEnum,
+    ) -> Result<String, FormatError<serde_json::Error>>
+    where
+        Self::VersionEnum: serde::Serialize,
+    {
+        if let Some(tag) = Self::known_version_tag(version) {
+            return Ok(tag.to_string());
+        }
+
+        let value = serde_json::to_value(version).map_err(FormatError::serialize)?;
+        match value.get(Self::VERSION_TAG) {
+            Some(serde_json::Value::String(version)) => Ok(version.clone()),
+            Some(serde_json::Value::Number(version)) if version.is_u64() => Ok(version.to_string()),
+            _ => Err(FormatError::deserialize(
+                <serde_json::Error as serde::de::Error>::missing_field(Self::VERSION_TAG),
+                config::capture_input().then(|| value.to_string()),
+            )),
+        }
+    }
+
+    /// Returns the version number of a version enum instance.
+    ///
+    /// Unlike [`Versioned::extract_version_string`], this does not allocate.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version enum instance
+    ///
+    /// # Returns
+    ///
+    /// The version number (e.g., "1", "2"), or "unknown" if it cannot be determined.
+    fn version_tag(version: &Self::VersionEnum) -> &'static str {
+        Self::known_version_tag(version).unwrap_or("unknown")
+    }
+
+    /// Returns the version number of a version enum instance, or `None` if its variant does not
+    /// determine it.
+    ///
+    /// The derive macro implements this with a match over the enum variants. Hand-written
+    /// version enums return `None` for variants that hold payloads of other versions, such as
+    /// an `Unknown(serde_json::Value)` variant, whose version
+    /// [`Versioned::try_extract_version`] then reads from the data.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version enum instance
+    ///
+    /// # Returns
+    ///
+    /// The version number (e.g., "1", "2"), or `None` if the variant does not determine it.
+    fn known_version_tag(_version: &Self::VersionEnum) -> Option<&'static str> {
+        None
+    }
+
+    /// Returns the version tag of a version enum instance as a JSON value.
+    ///
+    /// This is the tag as it appears in the serialized data, for building custom envelopes
+    /// that carry the version next to the payload. With `tag_type = "int"` the tag is a
+    /// number, and otherwise a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version enum instance
+    ///
+    /// # Returns
+    ///
+    /// The version tag, e.g. `"2"`, or `2` with integer tags.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # let user = user();
+    /// let tag = User::tag_value(&user.to_version());
+    /// assert_eq!(tag, serde_json::json!("2"));
+    /// ```
+    #[cfg(feature = "json")]
+    fn tag_value(version: &Self::VersionEnum) -> serde_json::Value {
+        serde_json::Value::from(Self::version_tag(version))
+    }
+
+    /// Returns how many versions data read in the given version can be written as.
+    ///
+    /// Any known version can be upgraded to the current struct and written as the latest
+    /// version or as any version it can be downgraded to (see [`Versioned::to_version_n`]),
+    /// so this is the same for every declared version, and `0` for unknown versions.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version number the data was read in (e.g., "1")
+    ///
+    /// # Returns
+    ///
+    /// The number of versions the data can be written as.
+    #[must_use]
+    fn version_count_reachable_from(_version: &str) -> usize {
+        // The derive macro overrides this with a match over the declared versions.
+        0
+    }
+
+    /// Returns every declared version number, oldest first.
+    ///
+    /// This is useful for listing the versions a type supports, or for checking that a
+    /// user-supplied version is one of them before calling [`Versioned::to_version_n`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// assert_eq!(User::all_versions(), ["1", "2"]);
+    /// assert!(User::all_versions().contains(&"2"));
+    /// ```
+    #[must_use]
+    fn all_versions() -> &'static [&'static str] {
+        // The derive macro overrides this with the declared versions.
+        &[]
+    }
+
+    /// Compares two version numbers, ordering older versions first.
+    ///
+    /// Versions are ordered by their position in the `versions` list, so unknown versions are not
+    /// comparable. The `version_parser` option of the `versioned` attribute replaces this with a
+    /// custom comparator for schemes such as dates, which can also order versions this build does
+    /// not know, e.g. to tell data written by a newer release.
+    ///
+    /// # Returns
+    ///
+    /// The ordering of `a` relative to `b`, or `None` if they cannot be compared.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// assert_eq!(User::compare_versions("1", "2"), Some(core::cmp::Ordering::Less));
+    /// assert_eq!(User::compare_versions("1", "9"), None);
+    /// ```
+    #[must_use]
+    fn compare_versions(a: &str, b: &str) -> Option<core::cmp::Ordering> {
+        let versions = Self::all_versions();
+        let position = |number: &str| versions.iter().position(|version| *version == number);
+        Some(position(a)?.cmp(&position(b)?))
+    }
+
+    /// Checks that a version of this struct and a version of another struct in the same group
+    /// have the same version number, e.g. for rejecting documents whose halves disagree.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `VersionConversionError` for the version of this struct if the two structs are
+    /// not in the same [`VERSION_GROUP`](Versioned::VERSION_GROUP) or the versions differ.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # let user_version = user().to_version();
+    /// # let profile_version = UserProfile { bio: String::new() }.to_version();
+    /// User::assert_same_version::<UserProfile>(&user_version, &profile_version)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn assert_same_version<O: Versioned>(
+        version: &Self::VersionEnum,
+        other: &O::VersionEnum,
+    ) -> Result<(), VersionConversionError> {
+        let (tag, other_tag) = (Self::version_tag(version), O::version_tag(other));
+        if Self::VERSION_GROUP.is_none() || Self::VERSION_GROUP != O::VERSION_GROUP {
+            return Err(VersionConversionError::new(
+                tag,
+                format!(
+                    "`{}` and `{}` are not in the same version group",
+                    core::any::type_name::<Self>(),
+                    core::any::type_name::<O>()
+                )
+                .into(),
+            ));
+        }
+        if tag != other_tag {
+            return Err(VersionConversionError::new(
+                tag,
+                format!(
+                    "version {tag} does not match version {other_tag} of `{}`",
+                    core::any::type_name::<O>()
+                )
+                .into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the versions an upgrade from the given version passes through to reach the latest.
+    ///
+    /// The path starts with `from` itself and ends with the latest version,
+    /// e.g. `upgrade_path("1") == ["1", "2"]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The version number to upgrade from (e.g., "1")
+    ///
+    /// # Returns
+    ///
+    /// The version numbers along the upgrade path, or an empty vector if `from` is unknown.
+    #[must_use]
+    fn upgrade_path(from: &str) -> Vec<&'static str> {
+        let versions = Self::all_versions();
+        versions
+            .iter()
+            .position(|version| *version == from)
+            .map_or_else(Vec::new, |start| versions[start..].to_vec())
+    }
+
+    /// Looks up the version number declared for a version struct type.
+    ///
+    /// This lets generic code that only has a version struct type find its version number.
+    /// See also [`VersionMember`], which provides the same number at compile time.
+    ///
+    /// # Returns
+    ///
+    /// The version number of `V`, or `None` if `V` is not one of the declared versions.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// assert_eq!(User::version_for::<UserV2>(), Some("2"));
+    /// assert_eq!(User::version_for::<String>(), None);
+    /// ```
+    #[must_use]
+    fn version_for<V: 'static>() -> Option<&'static str> {
+        // The derive macro overrides this with a lookup over the declared version structs.
+        None
+    }
+
+    /// Borrows the version struct held by a version enum if it is of type `V`.
+    ///
+    /// This lets generic code pull out one historical version without matching on the enum.
+    ///
+    /// # Returns
+    ///
+    /// The version struct, or `None` if `version` holds another version.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let version = UserVersion::Version1(UserV1 { name: "Alice".to_string() });
+    /// assert_eq!(User::as_version::<UserV1>(&version).unwrap().name, "Alice");
+    /// assert!(User::as_version::<UserV2>(&version).is_none());
+    /// ```
+    #[must_use]
+    fn as_version<V: 'static>(_version: &Self::VersionEnum) -> Option<&V> {
+        // The derive macro overrides this with a match over the variants.
+        None
+    }
+
+    /// Describes the version tag and every declared version along with its version struct.
+    ///
+    /// The manifest is serializable, so it can be written out and committed to catch schema
+    /// changes in review.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// let manifest = User::version_manifest();
+    /// assert_eq!(manifest.tag, "version");
+    /// assert_eq!(manifest.versions[0], ("1", core::any::type_name::<UserV1>()));
+    /// ```
+    #[must_use]
+    fn version_manifest() -> VersionManifest {
+        // The derive macro overrides this with the declared versions.
+        VersionManifest {
+            tag: Self::VERSION_TAG,
+            versions: Vec::new(),
+        }
+    }
+
+    /// Serializes the current struct to a string format via its versioned enum.
+    ///
+    /// This is a convenience method that converts the struct to its versioned enum
+    /// and then serializes it using the provided serializer function.
+    ///
+    /// # Arguments
+    ///
+    /// * `serializer` - A function that serializes `Self::VersionEnum` to the desired format
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(T)` - Successfully serialized data
+    /// * `Err(E)` - Error during serialization
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use serde_versioned::Versioned;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Versioned, Serialize, Deserialize, Clone)]
+    /// #[versioned(versions = [UserV1, UserV2])]
+    /// struct User {
+    ///     name: String,
+    ///     age: u32,
+    /// }
+    ///
+    /// #[derive(Serialize, Deserialize, Clone)]
+    /// struct UserV1 {
+    ///     name: String,
+    /// }
+    ///
+    /// #[derive(Serialize, Deserialize, Clone)]
+    /// struct UserV2 {
+    ///     name: String,
+    ///     age: u32,
+    /// }
+    ///
+    /// impl serde_versioned::FromVersion<User> for UserV1 {
+    ///     fn convert(self) -> User {
+    ///         User { name: self.name, age: 0 }
+    ///     }
+    /// }
+    ///
+    /// impl serde_versioned::FromVersion<User> for UserV2 {
+    ///     fn convert(self) -> User {
+    ///         User { name: self.name, age: self.age }
+    ///     }
+    /// }
+    ///
+    /// let user = User { name: "Alice".to_string(), age: 30 };
+    /// let json = user.to_format(serde_json::to_string).unwrap();
+    /// ```
+    fn to_format<F, T, E>(&self, serializer: F) -> Result<T, E>
+    where
+        F: FnOnce(&Self::VersionEnum) -> Result<T, E>,
     {
         let version = self.to_version();
         serializer(&version)
     }
+
+    /// Serializes the current struct into a writer.
+    ///
+    /// This is the counterpart of [`Versioned::to_format`] for files and sockets. The latest
+    /// version is written, as with [`Versioned::to_version`].
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The writer to serialize into
+    /// * `serializer` - A function that serializes `Self::VersionEnum` into the writer, such as
+    ///   `serde_json::to_writer`
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Serialize` if serialization fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # let user = user();
+    /// let file = std::fs::File::create("user.json").unwrap();
+    /// user.to_writer(std::io::BufWriter::new(file), serde_json::to_writer).unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    fn to_writer<W, F, E>(&self, writer: W, serializer: F) -> Result<(), FormatError<E>>
+    where
+        W: std::io::Write,
+        F: FnOnce(W, &Self::VersionEnum) -> Result<(), E>,
+        E: Error + Send + Sync + 'static,
+    {
+        let version = self.to_version();
+        serializer(writer, &version).map_err(FormatError::serialize)
+    }
+
+    /// Serializes the current struct into a binary format.
+    ///
+    /// This is the counterpart of [`Versioned::from_bytes`]. The latest version is written, as
+    /// with [`Versioned::to_version`].
+    ///
+    /// # Arguments
+    ///
+    /// * `serializer` - A function that serializes `Self::VersionEnum` into bytes, such as
+    ///   `rmp_serde::to_vec`
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Serialize` if serialization fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # let user = user();
+    /// let bytes = user.to_bytes(rmp_serde::to_vec).unwrap();
+    /// ```
+    fn to_bytes<F, E>(&self, serializer: F) -> Result<Vec<u8>, FormatError<E>>
+    where
+        F: FnOnce(&Self::VersionEnum) -> Result<Vec<u8>, E>,
+        E: Error + Send + Sync + 'static,
+    {
+        let version = self.to_version();
+        serializer(&version).map_err(FormatError::serialize)
+    }
+
+    /// Serializes the current struct as JSON.
+    ///
+    /// Shorthand for [`Versioned::to_format`] with `serde_json::to_string`. The latest version is written, as
+    /// with [`Versioned::to_version`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Serialize` if serialization fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # let user = user();
+    /// let json = user.to_json().unwrap();
+    /// ```
+    #[cfg(feature = "json")]
+    fn to_json(&self) -> Result<String, FormatError<serde_json::Error>>
+    where
+        Self::VersionEnum: serde::Serialize,
+    {
+        self.to_format(serde_json::to_string)
+            .map_err(FormatError::serialize)
+    }
+
+    /// Serializes the current struct as YAML.
+    ///
+    /// Shorthand for [`Versioned::to_format`] with `serde_yaml::to_string`. The latest version is written, as
+    /// with [`Versioned::to_version`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Serialize` if serialization fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # let user = user();
+    /// let yaml = user.to_yaml().unwrap();
+    /// ```
+    #[cfg(feature = "yaml")]
+    fn to_yaml(&self) -> Result<String, FormatError<serde_yaml::Error>>
+    where
+        Self::VersionEnum: serde::Serialize,
+    {
+        self.to_format(serde_yaml::to_string)
+            .map_err(FormatError::serialize)
+    }
+
+    /// Serializes the current struct as TOML.
+    ///
+    /// Shorthand for [`Versioned::to_format`] with `toml::to_string`. The latest version is written, as
+    /// with [`Versioned::to_version`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Serialize` if serialization fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # let user = user();
+    /// let toml = user.to_toml().unwrap();
+    /// ```
+    #[cfg(feature = "toml")]
+    fn to_toml(&self) -> Result<String, FormatError<toml::ser::Error>>
+    where
+        Self::VersionEnum: serde::Serialize,
+    {
+        self.to_format(toml::to_string)
+            .map_err(FormatError::serialize)
+    }
+
+    /// Serializes the current struct as MessagePack.
+    ///
+    /// Shorthand for [`Versioned::to_bytes`] with `rmp_serde::to_vec`. The latest version is
+    /// written, as with [`Versioned::to_version`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Serialize` if serialization fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # let user = user();
+    /// let bytes = user.to_binary().unwrap();
+    /// ```
+    #[cfg(feature = "binary")]
+    fn to_binary(&self) -> Result<Vec<u8>, FormatError<rmp_serde::encode::Error>>
+    where
+        Self::VersionEnum: serde::Serialize,
+    {
+        self.to_bytes(rmp_serde::to_vec)
+    }
+
+    /// Serializes the current struct and returns the output along with the version it was
+    /// written as.
+    ///
+    /// The version is always the latest one, since [`Versioned::to_version`] is used. Returning
+    /// both keeps the output and its version together for callers that log what they wrote.
+    ///
+    /// # Arguments
+    ///
+    /// * `serializer` - A function that serializes `Self::VersionEnum` to the desired format
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((T, &'static str))` - Successfully serialized data and its version number
+    /// * `Err(FormatError<E>)` - Error during serialization
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Serialize` if serialization fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use serde_versioned::{__example::*, Versioned};
+    /// # let user = user();
+    /// let (json, version) = user.to_format_tagged(serde_json::to_string).unwrap();
+    /// assert_eq!(version, "2");
+    /// ```
+    fn to_format_tagged<F, T, E>(&self, serializer: F) -> Result<(T, &'static str), FormatError<E>>
+    where
+        F: FnOnce(&Self::VersionEnum) -> Result<T, E>,
+        E: Error + Send + Sync + 'static,
+    {
+        let version = self.to_version();
+        let output = serializer(&version).map_err(FormatError::serialize)?;
+        Ok((output, Self::version_tag(&version)))
+    }
+}
+
+/// Machine-readable description of a versioned type, as returned by
+/// [`Versioned::version_manifest`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct VersionManifest {
+    /// The name of the field holding the version number (e.g., "version")
+    pub tag: &'static str,
+    /// Each version number paired with the type name of its version struct, oldest first
+    pub versions: Vec<(&'static str, &'static str)>,
+}
+
+/// A field whose value changed during a migration, as reported by
+/// [`Versioned::migrate_with_report`].
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// The name of the field
+    pub field: String,
+    /// The value before the migration, or `None` if the field was added
+    pub old: Option<serde_json::Value>,
+    /// The value after the migration, or `None` if the field was removed
+    pub new: Option<serde_json::Value>,
+}
+
+#[cfg(feature = "json")]
+impl FieldChange {
+    /// Compares the top-level fields of two JSON values, returning the changes sorted by field.
+    fn diff(old: &serde_json::Value, new: &serde_json::Value) -> Vec<Self> {
+        let empty = serde_json::Map::new();
+        let old = old.as_object().unwrap_or(&empty);
+        let new = new.as_object().unwrap_or(&empty);
+
+        let mut fields: Vec<&String> = old.keys().chain(new.keys()).collect();
+        fields.sort();
+        fields.dedup();
+        fields
+            .into_iter()
+            .filter(|field| old.get(*field) != new.get(*field))
+            .map(|field| Self {
+                field: field.clone(),
+                old: old.get(field).cloned(),
+                new: new.get(field).cloned(),
+            })
+            .collect()
+    }
 }
 
+/// Maximum number of bytes of input text kept in a [`VersionConversionError`].
+const MAX_CONVERSION_INPUT_LEN: usize = 1024;
+
 /// Error type for version conversion operations.
 ///
 /// This error provides detailed information about failures during version conversion,
@@ -330,6 +2146,12 @@ pub struct VersionConversionError {
     pub source: Box<dyn Error + Send + Sync + 'static>,
     /// Additional context about the conversion failure
     pub context: Option<String>,
+    /// The serialized input the failing version was read from, when read by `from_format` or
+    /// `from_bytes` (text is truncated to the first 1024 bytes)
+    pub input: Option<FormatInput>,
+    /// The position of `version` in the declared versions, oldest first, when it is a declared
+    /// version
+    pub version_index: Option<usize>,
 }
 
 impl VersionConversionError {
@@ -344,6 +2166,8 @@ impl VersionConversionError {
             version: version.into(),
             source,
             context: None,
+            input: None,
+            version_index: None,
         }
     }
 
@@ -363,6 +2187,8 @@ impl VersionConversionError {
             version: version.into(),
             source,
             context: Some(context.into()),
+            input: None,
+            version_index: None,
         }
     }
 
@@ -371,6 +2197,50 @@ impl VersionConversionError {
     pub fn version(&self) -> &str {
         &self.version
     }
+
+    /// Attaches the position of the failing version in the declared versions.
+    ///
+    /// Errors returned by the generated `from_version` carry it already.
+    #[must_use]
+    pub const fn with_version_index(mut self, index: usize) -> Self {
+        self.version_index = Some(index);
+        self
+    }
+
+    /// Returns the position of the failing version in the declared versions, oldest first.
+    ///
+    /// Unlike [`version`](Self::version), this orders the failing version without parsing it,
+    /// e.g. `error.version_index() < Some(cutoff)` for data older than a cutoff version.
+    #[must_use]
+    pub const fn version_index(&self) -> Option<usize> {
+        self.version_index
+    }
+
+    /// Attaches the serialized input the failing version was read from.
+    #[must_use]
+    pub fn with_input(mut self, input: FormatInput) -> Self {
+        self.input = Some(input);
+        self
+    }
+
+    /// Returns the serialized input the failing version was read from, if it was captured.
+    #[must_use]
+    pub const fn input(&self) -> Option<&FormatInput> {
+        self.input.as_ref()
+    }
+
+    /// Attaches `input` as text if capturing is enabled, truncated to
+    /// [`MAX_CONVERSION_INPUT_LEN`] bytes at a character boundary.
+    fn with_captured_text(self, input: &str) -> Self {
+        if !config::capture_input() {
+            return self;
+        }
+        let mut len = input.len().min(MAX_CONVERSION_INPUT_LEN);
+        while !input.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.with_input(FormatInput::Text(input[..len].to_string()))
+    }
 }
 
 impl Error for VersionConversionError {
@@ -379,17 +2249,56 @@ impl Error for VersionConversionError {
     }
 }
 
-impl std::fmt::Display for VersionConversionError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "Failed to convert from version {}: {}",
-            self.version, self.source
-        )?;
+impl core::fmt::Display for VersionConversionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Failed to convert from version {}", self.version)?;
         if let Some(ref context) = self.context {
             write!(f, " ({context})")?;
         }
-        Ok(())
+        write!(f, ": {}", self.source)
+    }
+}
+
+/// Input captured by [`FormatError::Deserialize`] for diagnosing a failed read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatInput {
+    /// The text that failed to deserialize
+    Text(String),
+    /// A summary of binary input that failed to deserialize, whose contents are not kept
+    Bytes {
+        /// The length of the input in bytes
+        len: usize,
+    },
+}
+
+impl FormatInput {
+    /// Returns the captured text, or `None` for binary input.
+    #[must_use]
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) => Some(text),
+            Self::Bytes { .. } => None,
+        }
+    }
+}
+
+impl core::fmt::Display for FormatInput {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Text(text) => {
+                // Truncate long inputs for readability, at a character boundary
+                if text.len() > 100 {
+                    let mut len = 100;
+                    while !text.is_char_boundary(len) {
+                        len -= 1;
+                    }
+                    write!(f, "{:?}...", &text[..len])
+                } else {
+                    write!(f, "{text:?}")
+                }
+            }
+            Self::Bytes { len } => write!(f, "{len} bytes"),
+        }
     }
 }
 
@@ -404,19 +2313,37 @@ pub enum FormatError<E> {
     Deserialize {
         /// The original deserialization error
         error: E,
-        /// The input string that failed to deserialize (if available)
-        input: Option<String>,
+        /// The input that failed to deserialize (if available)
+        input: Option<FormatInput>,
     },
     /// Error occurred during conversion from a versioned struct to the current struct.
     ///
     /// This variant contains detailed information about the version conversion failure.
     VersionConversion(VersionConversionError),
+    /// Error occurred during serialization of the versioned enum.
+    ///
+    /// This variant contains the original serialization error from the format serializer.
+    Serialize {
+        /// The original serialization error
+        error: E,
+    },
 }
 
 impl<E: Error + Send + Sync + 'static> FormatError<E> {
-    /// Creates a new `Deserialize` variant with the error and optional input.
-    pub const fn deserialize(error: E, input: Option<String>) -> Self {
-        Self::Deserialize { error, input }
+    /// Creates a new `Deserialize` variant with the error and optional input text.
+    pub fn deserialize(error: E, input: Option<String>) -> Self {
+        Self::Deserialize {
+            error,
+            input: input.map(FormatInput::Text),
+        }
+    }
+
+    /// Creates a new `Deserialize` variant with the error and a summary of the binary input.
+    pub const fn deserialize_bytes(error: E, input: &[u8]) -> Self {
+        Self::Deserialize {
+            error,
+            input: Some(FormatInput::Bytes { len: input.len() }),
+        }
     }
 
     /// Creates a new `VersionConversion` variant from a version conversion error.
@@ -427,6 +2354,55 @@ impl<E: Error + Send + Sync + 'static> FormatError<E> {
         Self::VersionConversion(VersionConversionError::new(version, source))
     }
 
+    /// Creates a new `Serialize` variant with the error.
+    pub const fn serialize(error: E) -> Self {
+        Self::Serialize { error }
+    }
+
+    /// Maps the format error type with the given function.
+    ///
+    /// The error of the `Deserialize` and `Serialize` variants is mapped, while the captured
+    /// input and the `VersionConversion` variant are left unchanged. This is useful
+    /// to unify errors from several formats (e.g. `FormatError<serde_json::Error>` and
+    /// `FormatError<serde_yaml::Error>`) into a common `FormatError<MyError>`.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A function converting the format error
+    pub fn map_deserialize<F, E2>(self, f: F) -> FormatError<E2>
+    where
+        F: FnOnce(E) -> E2,
+    {
+        match self {
+            Self::Deserialize { error, input } => FormatError::Deserialize {
+                error: f(error),
+                input,
+            },
+            Self::VersionConversion(e) => FormatError::VersionConversion(e),
+            Self::Serialize { error } => FormatError::Serialize { error: f(error) },
+        }
+    }
+
+    /// Erases the format error type, keeping the variant and the captured input.
+    ///
+    /// Unlike [`map_deserialize`](Self::map_deserialize), this needs no common error type, so
+    /// errors of every format can be collected together, e.g. in a `Vec<FormatError<ErasedError>>`.
+    #[must_use]
+    pub fn erase(self) -> FormatError<ErasedError> {
+        self.map_deserialize(ErasedError::new)
+    }
+
+    /// Returns the captured input of a `Deserialize` error, or of a `VersionConversion` error
+    /// returned by `from_format` or `from_bytes`.
+    #[must_use]
+    pub fn input(&self) -> Option<&FormatInput> {
+        match self {
+            Self::Deserialize { input, .. } => input.as_ref(),
+            Self::VersionConversion(e) => e.input(),
+            Self::Serialize { .. } => None,
+        }
+    }
+
     /// Returns `true` if this is a deserialization error.
     pub const fn is_deserialize(&self) -> bool {
         matches!(self, Self::Deserialize { .. })
@@ -436,35 +2412,233 @@ impl<E: Error + Send + Sync + 'static> FormatError<E> {
     pub const fn is_version_conversion(&self) -> bool {
         matches!(self, Self::VersionConversion(_))
     }
+
+    /// Returns `true` if this is a serialization error.
+    pub const fn is_serialize(&self) -> bool {
+        matches!(self, Self::Serialize { .. })
+    }
 }
 
 impl<E: Error + Send + Sync + 'static> Error for FormatError<E> {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
-            Self::Deserialize { error, .. } => Some(error),
+            Self::Deserialize { error, .. } | Self::Serialize { error } => Some(error),
             Self::VersionConversion(e) => e.source(),
         }
     }
 }
 
-impl<E: Error + Send + Sync + 'static> std::fmt::Display for FormatError<E> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<E: Error + Send + Sync + 'static> core::fmt::Display for FormatError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Deserialize { error, input } => {
                 write!(f, "Deserialization error: {error}")?;
-                if let Some(input_str) = input {
-                    // Truncate long inputs for readability
-                    if input_str.len() > 100 {
-                        write!(f, " (input: {:?}...)", &input_str[..100])?;
-                    } else {
-                        write!(f, " (input: {input_str:?})")?;
-                    }
+                if let Some(input) = input {
+                    write!(f, " (input: {input})")?;
                 }
                 Ok(())
             }
             Self::VersionConversion(e) => {
-                write!(f, "Version conversion error: {e}")
+                write!(f, "Version conversion error: {e}")?;
+                if let Some(input) = &e.input {
+                    write!(f, " (input: {input})")?;
+                }
+                Ok(())
+            }
+            Self::Serialize { error } => {
+                write!(f, "Serialization error: {error}")
+            }
+        }
+    }
+}
+
+/// A format error of any type, as held by errors returned by [`FormatError::erase`].
+///
+/// It displays as the original error and has the same source.
+#[derive(Debug)]
+pub struct ErasedError(Box<dyn Error + Send + Sync + 'static>);
+
+impl ErasedError {
+    /// Wraps a format error.
+    pub fn new(error: impl Error + Send + Sync + 'static) -> Self {
+        Self(Box::new(error))
+    }
+
+    /// Returns the original error if it is of type `T`.
+    #[must_use]
+    pub fn downcast_ref<T: Error + 'static>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+
+    /// Returns the original error.
+    #[must_use]
+    pub fn into_inner(self) -> Box<dyn Error + Send + Sync + 'static> {
+        self.0
+    }
+}
+
+impl Error for ErasedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl core::fmt::Display for ErasedError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Support code for the derive macro. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    use core::sync::atomic::AtomicBool;
+
+    pub use alloc::string::String;
+    pub use alloc::vec::Vec;
+    pub use alloc::{format, vec};
+
+    #[cfg(feature = "json")]
+    pub use serde_json;
+
+    pub use crate::unknown_version::UnknownVersionDeserializer;
+
+    #[cfg(feature = "clap")]
+    pub use clap;
+
+    #[cfg(feature = "proptest")]
+    pub use proptest;
+
+    /// Version tag of an enum with `tag_type = "int"`, written as an integer.
+    ///
+    /// A string of digits is read as well, since helpers such as `VersionedValue` insert the tag
+    /// as a string.
+    pub struct IntTag(pub u64);
+
+    impl<'de> serde::Deserialize<'de> for IntTag {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct IntTagVisitor;
+
+            impl serde::de::Visitor<'_> for IntTagVisitor {
+                type Value = IntTag;
+
+                fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                    formatter.write_str("an integer version")
+                }
+
+                fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+                    Ok(IntTag(value))
+                }
+
+                fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                    u64::try_from(value)
+                        .map(IntTag)
+                        .map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(value), &self))
+                }
+
+                fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                    value
+                        .parse()
+                        .map(IntTag)
+                        .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(value), &self))
+                }
             }
+
+            deserializer.deserialize_any(IntTagVisitor)
+        }
+    }
+
+    /// Returns `true` if `value` differs from its type's default value.
+    pub fn is_populated<T: Default + PartialEq>(value: &T) -> bool {
+        *value != T::default()
+    }
+
+    /// Names the structs of a failed conversion in the error's context, ahead of any context
+    /// the conversion gave itself.
+    pub fn add_conversion_context(
+        mut error: crate::VersionConversionError,
+        from: &'static str,
+        to: &'static str,
+    ) -> crate::VersionConversionError {
+        error.context = Some(match error.context.take() {
+            Some(context) => format!("{from} → {to}; {context}"),
+            None => format!("{from} → {to}"),
+        });
+        error
+    }
+
+    /// Sets the position of the error's version among `versions` unless it is already set.
+    pub fn index_conversion_error(
+        mut error: crate::VersionConversionError,
+        versions: &[&str],
+    ) -> crate::VersionConversionError {
+        if error.version_index.is_none() {
+            error.version_index = versions
+                .iter()
+                .position(|version| *version == error.version);
+        }
+        error
+    }
+
+    /// Records a `debug` event for a conversion by `from_version`, with the version it read,
+    /// the struct it converted to, and whether it succeeded.
+    ///
+    /// This is a no-op unless the `tracing` feature is enabled.
+    pub fn trace_conversion<T>(
+        version: &'static str,
+        to: &'static str,
+        result: &Result<T, crate::VersionConversionError>,
+    ) {
+        #[cfg(feature = "tracing")]
+        match result {
+            Ok(_) => tracing::debug!(
+                version,
+                to,
+                success = true,
+                "converted version {version} to `{to}`"
+            ),
+            Err(error) => tracing::debug!(
+                version,
+                to,
+                success = false,
+                %error,
+                "failed to convert version {version} to `{to}`"
+            ),
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = (version, to, result);
+    }
+
+    /// Records a `warn` event for a read of a deprecated version.
+    ///
+    /// This is a no-op unless the `tracing` feature is enabled.
+    pub fn warn_deprecated_version(version_struct: &'static str, version: &'static str) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            version,
+            version_struct,
+            "read deprecated version {version} (`{version_struct}`)"
+        );
+        #[cfg(not(feature = "tracing"))]
+        let _ = (version_struct, version);
+    }
+
+    /// Logs a warning that a deprecated field was populated, at most once per `once`.
+    ///
+    /// This is a no-op unless the `log` feature is enabled.
+    pub fn warn_deprecated_field(
+        once: &'static AtomicBool,
+        version_struct: &'static str,
+        field: &'static str,
+        version: &'static str,
+    ) {
+        #[cfg(feature = "log")]
+        if !once.swap(true, core::sync::atomic::Ordering::Relaxed) {
+            log::warn!(
+                "Field `{field}` of `{version_struct}` (version {version}) is deprecated but was populated"
+            );
         }
+        #[cfg(not(feature = "log"))]
+        let _ = (once, version_struct, field, version);
     }
 }
@@ -0,0 +1,127 @@
+//! A minimal view of a serialized version enum for reading its version tag.
+//!
+//! [`VersionProbe`] is deserialized in place of a version enum when only the version is needed,
+//! such as for routing documents by version. It keeps the top-level fields that hold a string or
+//! an integer, which covers any version tag, and skips everything else without deserializing it.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use core::fmt;
+use serde::Deserialize;
+use serde::de::{Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+
+/// Top-level string and integer fields of a serialized version enum.
+///
+/// Used by [`Versioned::peek_version`](crate::Versioned::peek_version). Nested values and other
+/// scalars are skipped, so deserializing a probe does not fail on malformed fields of a version.
+/// This requires a self-describing format such as JSON, YAML, or TOML.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VersionProbe {
+    fields: BTreeMap<String, String>,
+}
+
+impl VersionProbe {
+    /// Returns the value of the field `tag` (e.g., `"version"`), if it holds a string or an integer.
+    ///
+    /// Integer values are returned in their decimal form.
+    #[must_use]
+    pub fn tag(&self, tag: &str) -> Option<&str> {
+        self.fields.get(tag).map(String::as_str)
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionProbe {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ProbeVisitor;
+
+        impl<'de> Visitor<'de> for ProbeVisitor {
+            type Value = VersionProbe;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map with a version tag")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut fields = BTreeMap::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    if let Some(value) = map.next_value::<TagValue>()?.0 {
+                        fields.insert(key, value);
+                    }
+                }
+                Ok(VersionProbe { fields })
+            }
+        }
+
+        deserializer.deserialize_map(ProbeVisitor)
+    }
+}
+
+/// A field value kept as a string if it could be a version tag, and skipped otherwise.
+struct TagValue(Option<String>);
+
+impl<'de> Deserialize<'de> for TagValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TagValueVisitor;
+
+        impl<'de> Visitor<'de> for TagValueVisitor {
+            type Value = TagValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any value")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(TagValue(Some(value.to_string())))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+                Ok(TagValue(Some(value)))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(TagValue(Some(value.to_string())))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(TagValue(Some(value.to_string())))
+            }
+
+            fn visit_bool<E>(self, _: bool) -> Result<Self::Value, E> {
+                Ok(TagValue(None))
+            }
+
+            fn visit_f64<E>(self, _: f64) -> Result<Self::Value, E> {
+                Ok(TagValue(None))
+            }
+
+            fn visit_bytes<E>(self, _: &[u8]) -> Result<Self::Value, E> {
+                Ok(TagValue(None))
+            }
+
+            fn visit_unit<E>(self) -> Result<Self::Value, E> {
+                Ok(TagValue(None))
+            }
+
+            fn visit_none<E>(self) -> Result<Self::Value, E> {
+                Ok(TagValue(None))
+            }
+
+            fn visit_some<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<Self::Value, D::Error> {
+                TagValue::deserialize(deserializer)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                IgnoredAny.visit_seq(seq).map(|_| TagValue(None))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                IgnoredAny.visit_map(map).map(|_| TagValue(None))
+            }
+        }
+
+        deserializer.deserialize_any(TagValueVisitor)
+    }
+}
@@ -0,0 +1,406 @@
+//! Reporting of unknown versions by derived version enums.
+//!
+//! Serde reports a version tag that names no variant as an unknown variant of the version enum.
+//! The derive reads its enum through [`UnknownVersionDeserializer`], which passes the input
+//! through unchanged and replaces that error with one naming the version and the known versions.
+//! The tag is the only top-level value that the enum reads as an identifier (or, adjacently
+//! tagged, as a variant of itself), so the error is recognized by where it occurs rather than by
+//! its message, and the input is read only once.
+
+use alloc::string::String;
+use core::fmt;
+use serde::de::{DeserializeSeed, Deserializer, EnumAccess, Error, MapAccess, SeqAccess, Visitor};
+
+/// Forwards each `deserialize_*` method to the inner deserializer, passing the visitor through
+/// `$wrap`.
+macro_rules! forward_deserialize {
+    ($wrap:path; $($method:ident($($arg:ident: $ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error> {
+                self.inner.$method($($arg,)* $wrap(visitor, self.versions))
+            }
+        )*
+    };
+}
+
+/// Forwards each `visit_*` method taking a value to the inner visitor.
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method<E: Error>(self, value: $ty) -> Result<Self::Value, E> {
+                self.inner.$method(value)
+            }
+        )*
+    };
+}
+
+/// Deserializer for a version enum that reports a tag naming no version as an unknown version.
+pub struct UnknownVersionDeserializer<D> {
+    inner: D,
+    versions: Versions,
+}
+
+impl<D> UnknownVersionDeserializer<D> {
+    /// Wraps the deserializer of the version enum named `name` (as serde names it), whose
+    /// versions are `known` as they appear in the error, e.g. ``"`1`, `2`"``.
+    pub fn new(inner: D, name: &'static str, known: &'static str) -> Self {
+        Self {
+            inner,
+            versions: Versions { name, known },
+        }
+    }
+}
+
+/// The version enum being read.
+#[derive(Clone, Copy)]
+struct Versions {
+    /// Name of the enum, which an adjacently tagged enum passes when reading its tag
+    name: &'static str,
+    /// Versions listed in the error for an unknown version
+    known: &'static str,
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for UnknownVersionDeserializer<D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        EnumVisitor::new;
+        deserialize_any(),
+        deserialize_bool(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_i128(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_u128(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier(),
+        deserialize_ignored_any(),
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Visitor of the version enum, or of the tag of an adjacently tagged one, which reads the
+/// values of its map or sequence, or its variant, through [`TagSeed`].
+struct EnumVisitor<V> {
+    inner: V,
+    versions: Versions,
+}
+
+impl<V> EnumVisitor<V> {
+    fn new(inner: V, versions: Versions) -> Self {
+        Self { inner, versions }
+    }
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for EnumVisitor<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool(bool),
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_i128(i128),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_u128(u128),
+        visit_f32(f32),
+        visit_f64(f64),
+        visit_char(char),
+        visit_str(&str),
+        visit_borrowed_str(&'de str),
+        visit_string(String),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(alloc::vec::Vec<u8>),
+    }
+
+    fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.inner.visit_some(deserializer)
+    }
+
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        self.inner.visit_newtype_struct(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_seq(TagAccess {
+            inner: seq,
+            versions: self.versions,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_map(TagAccess {
+            inner: map,
+            versions: self.versions,
+        })
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_enum(TagAccess {
+            inner: data,
+            versions: self.versions,
+        })
+    }
+}
+
+/// Map, sequence, or variant access of the version enum, whose values, elements, or variant are
+/// read through [`TagSeed`].
+struct TagAccess<A> {
+    inner: A,
+    versions: Versions,
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for TagAccess<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        self.inner.next_key_seed(seed)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, Self::Error> {
+        self.inner.next_value_seed(TagSeed {
+            inner: seed,
+            versions: self.versions,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for TagAccess<A> {
+    type Error = A::Error;
+    type Variant = A::Variant;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Self::Variant), Self::Error> {
+        self.inner.variant_seed(TagSeed {
+            inner: seed,
+            versions: self.versions,
+        })
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for TagAccess<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, Self::Error> {
+        self.inner.next_element_seed(TagSeed {
+            inner: seed,
+            versions: self.versions,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+/// Seed of a top-level value of the version enum, which may be its tag.
+struct TagSeed<S> {
+    inner: S,
+    versions: Versions,
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for TagSeed<S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.inner.deserialize(ValueDeserializer {
+            inner: deserializer,
+            versions: self.versions,
+        })
+    }
+}
+
+/// Deserializer of a top-level value of the version enum, which passes every value through
+/// unchanged except the tag, the one value read as an identifier (or as a variant of the enum
+/// itself, when adjacently tagged).
+struct ValueDeserializer<D> {
+    inner: D,
+    versions: Versions,
+}
+
+/// Passes a visitor of a value other than the tag through unchanged.
+fn unchanged<V>(visitor: V, _versions: Versions) -> V {
+    visitor
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for ValueDeserializer<D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        unchanged;
+        deserialize_any(),
+        deserialize_bool(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_i128(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_u128(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        deserialize_ignored_any(),
+    }
+
+    // An adjacently tagged enum reads its tag as a variant of itself
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if name == self.versions.name {
+            self.inner
+                .deserialize_enum(name, variants, EnumVisitor::new(visitor, self.versions))
+        } else {
+            self.inner.deserialize_enum(name, variants, visitor)
+        }
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.inner.deserialize_identifier(TagVisitor {
+            inner: visitor,
+            versions: self.versions,
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Visitor of the tag, which reports a version naming no variant as an unknown version.
+struct TagVisitor<V> {
+    inner: V,
+    versions: Versions,
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for TagVisitor<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool(bool),
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_i128(i128),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_u128(u128),
+        visit_f32(f32),
+        visit_f64(f64),
+        visit_char(char),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(alloc::vec::Vec<u8>),
+    }
+
+    fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+        let known = self.versions.known;
+        self.inner
+            .visit_str::<E>(value)
+            .map_err(|_| unknown_version(value, known))
+    }
+
+    fn visit_borrowed_str<E: Error>(self, value: &'de str) -> Result<Self::Value, E> {
+        let known = self.versions.known;
+        self.inner
+            .visit_borrowed_str::<E>(value)
+            .map_err(|_| unknown_version(value, known))
+    }
+
+    // Read as a `&str`, so that the version is still at hand for the error
+    fn visit_string<E: Error>(self, value: String) -> Result<Self::Value, E> {
+        self.visit_str(&value)
+    }
+}
+
+/// Returns the error for a version tag naming none of the `known` versions.
+fn unknown_version<E: Error>(version: &str, known: &str) -> E {
+    E::custom(format_args!(
+        "unknown version `{version}`, expected one of {known}"
+    ))
+}
@@ -0,0 +1,97 @@
+//! A loosely-typed versioned value for dynamic storage.
+//!
+//! [`VersionedValue`] holds a version number and the version's fields as JSON, without knowing
+//! the concrete type. Storage layers that hold many versioned types can keep them in this form
+//! and convert into the concrete type only when it is needed.
+
+use crate::{FormatError, Versioned, config};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// A version number paired with the version's fields as a JSON value.
+///
+/// This is the loosely-typed counterpart of a generated version enum.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use serde_versioned::{__example::*, Versioned};
+/// use serde_versioned::VersionedValue;
+///
+/// let stored = VersionedValue::new("1", serde_json::json!({ "name": "Alice" }));
+/// let user: User = stored.into_typed().unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionedValue {
+    /// The version number (e.g., "1", "2")
+    pub version: String,
+    /// The fields of the version, without the version tag (for an adjacently tagged version
+    /// enum, the value under its content key)
+    pub value: serde_json::Value,
+}
+
+impl VersionedValue {
+    /// Creates a new `VersionedValue` from a version number and the version's fields.
+    pub fn new(version: impl Into<String>, value: serde_json::Value) -> Self {
+        Self {
+            version: version.into(),
+            value,
+        }
+    }
+
+    /// Creates a `VersionedValue` holding the latest version of a versioned struct.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Serialize` if the version enum cannot be represented as JSON.
+    pub fn from_typed<T>(current: &T) -> Result<Self, FormatError<serde_json::Error>>
+    where
+        T: Versioned,
+        T::VersionEnum: Serialize,
+    {
+        let version = current.to_version();
+        let mut value = serde_json::to_value(&version).map_err(FormatError::serialize)?;
+        if let Some(fields) = value.as_object_mut() {
+            match T::VERSION_CONTENT {
+                Some(content) => value = fields.remove(content).unwrap_or_default(),
+                None => {
+                    fields.remove(T::VERSION_TAG);
+                }
+            }
+        }
+        Ok(Self::new(T::version_tag(&version), value))
+    }
+
+    /// Converts this value into the current struct of a versioned type.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FormatError::Deserialize` if the value is not an object or does not match the
+    /// version, or `FormatError::VersionConversion` if version conversion fails.
+    pub fn into_typed<T>(self) -> Result<T, FormatError<serde_json::Error>>
+    where
+        T: Versioned,
+        T::VersionEnum: DeserializeOwned,
+    {
+        let tagged = if let Some(content) = T::VERSION_CONTENT {
+            serde_json::json!({ T::VERSION_TAG: self.version, content: self.value })
+        } else {
+            let serde_json::Value::Object(mut fields) = self.value else {
+                return Err(FormatError::deserialize(
+                    serde::de::Error::custom("expected the version fields to be an object"),
+                    config::capture_input().then(|| self.value.to_string()),
+                ));
+            };
+            fields.insert(
+                T::VERSION_TAG.to_string(),
+                serde_json::Value::String(self.version),
+            );
+            serde_json::Value::Object(fields)
+        };
+
+        let version = T::VersionEnum::deserialize(&tagged).map_err(|e| {
+            FormatError::deserialize(e, config::capture_input().then(|| tagged.to_string()))
+        })?;
+        T::from_version(version).map_err(FormatError::VersionConversion)
+    }
+}
@@ -7,14 +7,27 @@
 
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
-use syn::{Data, DataStruct, DeriveInput, Fields, Meta, parse_macro_input};
+use quote::{ToTokens, quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{Data, DataStruct, DeriveInput, Fields, parse_macro_input};
 
 /// Derives the `Versioned` trait for a struct.
 ///
 /// This macro generates:
-/// - A version enum (e.g., `UserVersion`) with variants for each version
+/// - A version enum (e.g., `UserVersion`) with variants for each version, deriving `Clone`
 /// - Implementation of `Versioned` trait with `from_version` and `to_version` methods
+/// - Implementations of `VersionMember` mapping each version struct to its version number
+/// - Implementations of `From<VersionStruct>` for the version enum, wrapping each version struct
+///   in its variant
+/// - An inherent `LATEST_VERSION` constant holding the version number of the latest version
+/// - An inherent `discriminant` method mapping each version to a stable integer
+/// - An inherent `from_legacy_v1` method for untagged records of the first version
+/// - An inherent `from_body_and_version` method for untagged bodies whose version is stored separately
+/// - Inherent `as_version_1`, `as_version_2`, ... methods on the enum, borrowing the version
+///   struct of the variant without converting it
+/// - An inherent `into_latest` method returning the latest version struct without conversion
+/// - An inherent `from_latest` method converting the latest version struct to the current struct
 ///
 /// # Attributes
 ///
@@ -23,16 +36,145 @@ use syn::{Data, DataStruct, DeriveInput, Fields, Meta, parse_macro_input};
 /// #[versioned(versions = [Version1, Version2, ...])]
 /// ```
 ///
+/// Each entry of `versions` may carry serde variant attributes for its enum variant, written
+/// before or after the struct name, e.g. `[UserV1 #[serde(alias = "v1")], UserV2]`. Container
+/// attributes such as `deny_unknown_fields` belong on the version struct itself.
+///
+/// Versions are tagged `"1"`, `"2"`, ... by position. To keep tags stable when versions are
+/// removed or reordered, give them explicitly: `[UserV1 = "1.0", UserV2 = "2.0"]`. The enum
+/// variants are still named by position (`Version1`, `Version2`, ...).
+///
+/// Additional options can follow the `versions` list, separated by commas:
+///
+/// - `async` - Also generates `async fn from_version_async`, which converts through each
+///   version's `AsyncFromVersion` implementation (requires the `async` feature of `serde_versioned`)
+/// - `context = "MigrationContext"` - Also generates `from_version_with`, which converts through
+///   each version's `FromVersionWith<Self, MigrationContext>` implementation with a context given
+///   by the caller, for migrations that fill in fields from it. `from_version_with` cannot fail,
+///   so this cannot be combined with `validate`
+/// - `tag = "v"` - Key of the version tag in the serialized data (defaults to `"version"`)
+/// - `visibility = "pub(crate)"` - Visibility of the generated enum (defaults to the struct's visibility).
+///   The enum appears in the struct's public `Versioned` implementation, so it is defined as a
+///   public, `#[doc(hidden)]` `__UserVersion` and only the `UserVersion` alias gets this
+///   visibility. The hidden enum can still be named from outside the module
+/// - `enum_name = "UserSchema"` - Name of the generated enum (defaults to `{Struct}Version`), for
+///   when that name is already taken
+/// - `enum = UserVersion` - Implements `Versioned` against an existing enum instead of generating
+///   one, for full control over the serialized representation. Its variants must be named
+///   `Version1`, `Version2`, ... in the order of `versions`, each holding that version struct, and
+///   `tag` and `repr` must describe how it is tagged. The `From` conversions and `as_version_N`
+///   accessors of a generated enum are not added to it. Options that only shape the generated enum
+///   (`enum_name`, `visibility`, `derive`, `tag_type` and `unknown_as`) cannot be combined with it
+/// - `identity` - Generates `FromVersion` for the latest version struct by moving its fields into
+///   the current struct, for when the latest version has the same shape as the current struct
+///   (with `use_from`, `From<LatestVersion>` for the current struct instead)
+/// - `deprecated_fields = [UserV1::nickname, ...]` - Logs a one-time warning when a record is read
+///   whose listed field is not the default value (the field type must implement `Default` and
+///   `PartialEq`; warnings are emitted with the `log` feature of `serde_versioned`)
+/// - `snapshot(UserV1 = "...", ...)` - Generates a test asserting that the JSON of each listed
+///   version struct's `Default` value matches the given string, guarding the wire format
+///   (requires the `json` feature of `serde_versioned`)
+/// - `clap` - Also generates `value_parser`, a `clap` value parser accepting the declared version
+///   numbers, for selecting an output version on the command line (requires the `clap` feature of
+///   `serde_versioned`)
+/// - `chained` - Upgrades each version one step at a time instead of converting it to the current
+///   struct directly: each version struct implements `FromVersion` (or `TryFromVersion`) for the
+///   next listed version struct, and only the latest implements it for the current struct.
+///   `from_version_async` still converts each version directly
+/// - `accept = "1..=2"` - Makes `from_version` (and so `from_format`) reject versions outside the
+///   given range of version numbers with a `VersionConversionError`, to pin the versions a service
+///   accepts (requires integer version numbers). Rejected versions are not converted, so they need
+///   no `FromVersion` implementation
+/// - `downgrades = [UserV1, ...]` - Makes `to_version_n` write the listed older versions through
+///   each version struct's `IntoVersion` implementation
+/// - `proptest` - Also implements `proptest::arbitrary::Arbitrary` for the enum and generates
+///   `version_strategy`, a `proptest` strategy producing arbitrary values of every version, for
+///   property tests over migrations (each version struct must implement `Arbitrary`, and the enum
+///   additionally derives `Debug`; requires the `proptest` feature of `serde_versioned`)
+/// - `use_from` - Converts each version with its `From`/`Into` implementation instead of
+///   `FromVersion`, so existing `impl From<UserV1> for User` conversions can be used as they are.
+///   With `chained`, each version converts `Into` the next. Conversions cannot fail in this mode
+/// - `samples` - Also generates `all_samples`, returning each version's default value converted
+///   to the current struct, for showing what every version looks like after upgrading (each
+///   version struct must implement `Default`)
+/// - `test_roundtrip` - Also generates `assert_roundtrip` under `#[cfg(test)]`, which writes a value
+///   as JSON, reads it back and asserts that it is unchanged, catching fields that a migration
+///   drops or renames (requires `PartialEq` and `Debug` on the struct and the `json` feature of
+///   `serde_versioned`). With `test_roundtrip = "User::sample"`, a test is also generated that
+///   calls it with the value the given function returns
+/// - `read_only_versions = ["2", ...]` - Compiles conversions into `from_version` only for the listed
+///   version numbers; reading any other version is a `VersionConversionError`. This trims migration
+///   code from binaries that only read recent versions
+/// - `derive_serde = "serialize"` - Which serde traits the generated enum derives: `"serialize"`,
+///   `"deserialize"`, or `"both"` (the default). Services that only write or only read versioned
+///   data can drop the other direction; `from_body_and_version` is generated only with
+///   `Deserialize`, and `snapshot` requires `Serialize`
+/// - `validate = "User::validate"` - Calls the given `fn(&Self) -> Result<(), VersionConversionError>`
+///   on the converted value at the end of `from_version` (and `from_version_async`), so invariants are checked in one place
+///   for every version
+/// - `to_latest = "User::to_latest"` - Writes the current struct as the latest version with the
+///   given `fn(&Self) -> UserVersion` in `to_version` and `into_version`, instead of copying each
+///   field into the latest version struct. The latest version struct may then hold derived fields
+///   or differ from the current struct in its field names
+/// - `current = UserV2` - Writes the current struct as the given version instead of the last one
+///   listed, so a newer version struct can be listed (and read) before it is released. `to_version`,
+///   `LATEST_VERSION`, `identity`, `into_latest`, and `from_latest` then refer to this version
+/// - `group = "user_schema"` - Marks structs whose versions move in lockstep, such as the halves
+///   of one document. It sets `VERSION_GROUP` and generates `group_name`, and
+///   `Versioned::assert_same_version` checks that two structs of a group were read in the same version
+/// - `version_parser = "compare_dates"` - Orders version numbers with the given
+///   `fn(&str, &str) -> Ordering` in `compare_versions`, for schemes such as dates
+///   (`"2024.06"`). The versions are still listed oldest first, and a generated test checks the
+///   list against the comparator so the last one is the latest
+/// - `derive(Debug, PartialEq)` - Also derives the listed traits on the generated enum, e.g. to
+///   compare version payloads with `assert_eq!`. Traits the enum already derives (`Clone` and the
+///   serde traits) are not derived twice. Each version struct must implement them as well
+/// - `tag_type = "int"` - Writes the version tag as an integer (`{"version":2,...}`) instead of a
+///   string, for consumers that expect a number, and `tag_value` returns it as a JSON number.
+///   Every version number must be an integer, and the tag is also read from a string of digits.
+///   The version's fields are buffered as JSON while
+///   reading, so this requires the `json` feature of `serde_versioned` and the internal
+///   representation; serde attributes on the listed versions are not applied
+/// - `unknown_as = "2"` - Reads a version this build does not know as the given version, on a
+///   best-effort basis, so older readers keep working against data from newer producers. Fields
+///   the given version does not declare are dropped, and if it cannot read the data either, the
+///   error names the unknown version and the fallback's error. This has the same requirements
+///   and limits as `tag_type = "int"`
+/// - `deprecated = [UserV1, ...]` - Logs a warning each time `from_version` (or
+///   `from_version_async`) reads one of the listed versions, for retiring old schemas (warnings are
+///   emitted with the `tracing` feature of `serde_versioned`). With `deny_deprecated`, reading them
+///   is a `VersionConversionError` instead, so they need no `FromVersion` (or `AsyncFromVersion`)
+///   implementation
+/// - `repr = "adjacent", content = "data"` - Writes the version's fields under the `content` key
+///   next to the tag (`{"version":"2","data":{...}}`) instead of beside it, so version structs need
+///   not serialize as maps. `repr = "internal"` is the default, and `content` requires `adjacent`
+///
+/// The generated enum is internally tagged by default, so it can be embedded in a larger struct
+/// with `#[serde(flatten)]` as long as no version struct uses `#[serde(deny_unknown_fields)]`.
+///
 /// # Requirements
 ///
-/// - The struct must have named fields (not tuple structs or unit structs)
-/// - Each version struct must implement `FromVersion<CurrentStruct>`
-/// - Each version struct must implement `Serialize`, `Deserialize`, and `Clone`
+/// - The struct must have fields (not a unit struct). For a tuple struct, the latest version
+///   struct must be a tuple struct with the same number of fields
+/// - A generic struct passes its type parameters to every version struct (`UserV1<T>`), which
+///   must take the same parameters. Bounds go in the struct's where clause, and the type
+///   parameters must be `'static`. Lifetime parameters are not supported
+/// - Each version struct must implement `FromVersion<CurrentStruct>` or, for conversions that can
+///   fail, `TryFromVersion<CurrentStruct>` (with `chained`, for the next version struct instead).
+///   With `use_from`, it must implement `Into<CurrentStruct>` instead
+/// - Each version struct must implement `Clone`, and `Serialize` and `Deserialize` for the
+///   directions selected by `derive_serde`
+/// - Each field of the struct must convert into the same-named field of the latest version
+///   struct via `Into` (a field missing from the latest version struct, or of an incompatible
+///   type, is reported on the struct's field), unless `to_latest` writes the latest version.
+///   A field marked `#[versioned(rename_to = "age_years")]` converts into the named field instead,
+///   and one marked `#[versioned(skip)]` is left out, for runtime-only fields (with `identity`, it
+///   is set to its `Default` value). Field options are only supported on named fields
 ///
-/// # Panics
+/// # Errors
 ///
-/// This function will panic if `versions` is empty (which should be caught during compilation),
-/// or if the latest version cannot be determined.
+/// Malformed options, including a missing or empty `versions` list, are reported as compile
+/// errors instead of panicking.
 ///
 /// # Example
 ///
@@ -77,14 +219,22 @@ pub fn derive_versioned(input: TokenStream) -> TokenStream {
     let struct_name = &input.ident;
     let vis = &input.vis;
 
-    // Generate the version enum name (e.g., UserVersion for struct User)
-    let version_enum_name = syn::Ident::new(&format!("{struct_name}Version"), struct_name.span());
+    // Extract version structs and options from the versioned attribute
+    let args = match extract_args(&input) {
+        Ok(args) => args,
+        Err(err) => return err.to_compile_error().into(),
+    };
 
-    // Extract version structs from the versioned attribute
-    let versions = extract_versions(&input);
+    // Generate the version enum name (e.g., UserVersion for struct User) unless overridden
+    let version_enum_name = args
+        .existing_enum
+        .clone()
+        .or_else(|| args.enum_name.clone())
+        .unwrap_or_else(|| syn::Ident::new(&format!("{struct_name}Version"), struct_name.span()));
+    let versions = &args.versions;
 
-    // Validate that at least one version is specified
-    if versions.is_empty() {
+    // Validate that at least one version is specified, which makes the last one the latest
+    let Some(last_version) = versions.last() else {
         return syn::Error::new(
             struct_name.span(),
             format!(
@@ -93,233 +243,2427 @@ pub fn derive_versioned(input: TokenStream) -> TokenStream {
         )
         .to_compile_error()
         .into();
+    };
+    // The version the current struct is written as, which is the latest unless `current` picks
+    // an earlier one so that newer versions can be listed before they are released
+    let current_version = match &args.current {
+        Some(current) => match versions.iter().find(|version| version.ident == *current) {
+            Some(version) => version,
+            None => {
+                return syn::Error::new(
+                    current.span(),
+                    format!("`{current}` is not listed in `versions`. The current version must be one of the listed versions."),
+                )
+                .to_compile_error()
+                .into();
+            }
+        },
+        None => last_version,
+    };
+    let VersionEntry {
+        number: latest_version_num,
+        ident: latest_version_struct,
+        variant: latest_version_ident,
+        ..
+    } = current_version;
+
+    // Validate that deprecated fields belong to a listed version
+    if let Some((deprecated_struct, _)) =
+        args.deprecated_fields
+            .iter()
+            .find(|(deprecated_struct, _)| {
+                !versions
+                    .iter()
+                    .any(|version| version.ident == *deprecated_struct)
+            })
+    {
+        return syn::Error::new(
+            deprecated_struct.span(),
+            format!(
+                "`{deprecated_struct}` is not listed in `versions`. Deprecated fields must be given as `VersionStruct::field` for one of the listed versions."
+            ),
+        )
+        .to_compile_error()
+        .into();
     }
 
-    // Generate enum variants for each version (e.g., Version1(UserV1), Version2(UserV2))
-    let version_variants: Vec<_> = versions
-        .iter()
-        .map(|(version_num, version_struct)| {
-            let version_ident =
-                syn::Ident::new(&format!("Version{version_num}"), version_struct.span());
-            quote! {
-                #[serde(rename = #version_num)]
-                #version_ident(#version_struct)
-            }
+    // Validate that snapshots belong to a listed version
+    if let Some((snapshot_struct, _)) = args.snapshots.iter().find(|(snapshot_struct, _)| {
+        !versions
+            .iter()
+            .any(|version| version.ident == *snapshot_struct)
+    }) {
+        return syn::Error::new(
+            snapshot_struct.span(),
+            format!("`{snapshot_struct}` is not listed in `versions`. Snapshots must be given for the listed versions."),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    // Validate that downgrades belong to a listed version
+    if let Some(downgrade_struct) = args.downgrades.iter().find(|downgrade_struct| {
+        !versions
+            .iter()
+            .any(|version| version.ident == **downgrade_struct)
+    }) {
+        return syn::Error::new(
+            downgrade_struct.span(),
+            format!("`{downgrade_struct}` is not listed in `versions`. Downgrades must be given for the listed versions."),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    // Validate that deprecated versions belong to a listed version
+    if let Some(deprecated_struct) = args.deprecated.iter().find(|deprecated_struct| {
+        !versions
+            .iter()
+            .any(|version| version.ident == **deprecated_struct)
+    }) {
+        return syn::Error::new(
+            deprecated_struct.span(),
+            format!("`{deprecated_struct}` is not listed in `versions`. Deprecations must be given for the listed versions."),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    // Validate that readable versions are listed version numbers
+    if let Some(read_only_versions) = &args.read_only_versions
+        && let Some(unknown) = read_only_versions.iter().find(|number| {
+            !versions
+                .iter()
+                .any(|version| version.number == number.value())
         })
-        .collect();
+    {
+        return syn::Error::new(
+            unknown.span(),
+            format!(
+                "Version `{}` is not declared. `read_only_versions` must list numbers of the listed versions.",
+                unknown.value()
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
 
-    // Generate the version enum definition
-    let version_enum = quote! {
-        #[derive(serde::Serialize, serde::Deserialize)]
-        #[serde(tag = "version")]
-        #vis enum #version_enum_name {
-            #(#version_variants),*
+    // Validate that no option shapes the enum when an existing one is used
+    if let Some(existing_enum) = &args.existing_enum {
+        let conflict = [
+            ("enum_name", args.enum_name.is_some()),
+            ("visibility", args.enum_visibility.is_some()),
+            ("derive", !args.derives.is_empty()),
+            ("tag_type", args.tag_type.is_some()),
+            ("unknown_as", args.unknown_as.is_some()),
+        ]
+        .into_iter()
+        .find_map(|(option, set)| set.then_some(option));
+        if let Some(option) = conflict {
+            return syn::Error::new(
+                existing_enum.span(),
+                format!(
+                    "`{option}` configures the generated enum and cannot be combined with `enum`."
+                ),
+            )
+            .to_compile_error()
+            .into();
         }
-    };
+    }
 
-    // Generate match arms for from_version implementation
-    // Each arm converts the version struct and wraps any error in VersionConversionError
-    let from_version_match_arms: Vec<_> = versions
-        .iter()
-        .map(|(version_num, version_struct)| {
-            let version_ident =
-                syn::Ident::new(&format!("Version{version_num}"), version_struct.span());
-            quote! {
-                #version_enum_name::#version_ident(v) => {
-                    Ok(serde_versioned::FromVersion::convert(v))
-                },
-            }
-        })
-        .collect();
+    // Validate that conversions with a context, which cannot fail, are not validated
+    if let (Some(validate), Some(_)) = (&args.validate, &args.context) {
+        return syn::Error::new(
+            validate.span(),
+            "`validate` cannot be combined with `context`, since `from_version_with` cannot fail. Call the validation function on the value it returns instead.",
+        )
+        .to_compile_error()
+        .into();
+    }
 
-    // Generate match arms for extract_version_string implementation
-    let extract_version_match_arms: Vec<_> = versions
-        .iter()
-        .map(|(version_num, version_struct)| {
-            let version_ident =
-                syn::Ident::new(&format!("Version{version_num}"), version_struct.span());
-            let version_num_lit = syn::LitStr::new(version_num, version_struct.span());
-            quote! {
-                #version_enum_name::#version_ident(_) => #version_num_lit.to_string(),
-            }
-        })
-        .collect();
+    // Validate that the roundtrip helper can write and read the enum
+    if let Some(span) = args.test_roundtrip
+        && !matches!(args.derive_serde, SerdeDerive::Both)
+    {
+        return syn::Error::new(
+            span,
+            "`test_roundtrip` requires `Serialize` and `Deserialize` on the version enum. Remove `derive_serde` or set it to \"both\".",
+        )
+        .to_compile_error()
+        .into();
+    }
 
-    // Get the latest version for to_version implementation
-    let (latest_version_num, latest_version_struct) = versions.last().unwrap();
-    let latest_version_ident = syn::Ident::new(
-        &format!("Version{latest_version_num}"),
-        latest_version_struct.span(),
-    );
+    // Validate that snapshots can serialize the enum
+    if let Some((snapshot_struct, _)) = args.snapshots.first()
+        && !args.derive_serde.serialize()
+    {
+        return syn::Error::new(
+            snapshot_struct.span(),
+            "`snapshot` requires `Serialize` on the version enum. Remove `derive_serde` or set it to \"serialize\" or \"both\".",
+        )
+        .to_compile_error()
+        .into();
+    }
 
-    // Extract field names for cloning into the latest version struct
-    let fields = match &input.data {
-        Data::Struct(DataStruct {
-            fields: Fields::Named(fields),
-            ..
-        }) => fields
-            .named
-            .iter()
-            .map(|f| {
-                let field_name = &f.ident;
-                quote! { #field_name: self.#field_name.clone() }
-            })
-            .collect::<Vec<_>>(),
-        Data::Struct(DataStruct {
-            fields: Fields::Unnamed(_),
-            ..
-        }) => {
+    // Validate that `content` is given exactly with the adjacent representation
+    match (args.repr, &args.content) {
+        (Repr::Internal, Some(content)) => {
             return syn::Error::new(
-                struct_name.span(),
-                format!(
-                    "{struct_name}: Versioned derive macro only supports structs with named fields (not tuple structs).\n\nConsider changing:\n  struct {struct_name}(...);\n\nto:\n  struct {struct_name} {{ ... }};"
-                )
+                content.span(),
+                "`content` is only used with `repr = \"adjacent\"`.",
             )
             .to_compile_error()
             .into();
         }
-        Data::Struct(DataStruct {
-            fields: Fields::Unit,
-            ..
-        }) => {
+        (Repr::Adjacent, None) => {
             return syn::Error::new(
                 struct_name.span(),
-                format!(
-                    "{struct_name}: Versioned derive macro does not support unit structs. The struct must have at least one named field."
-                )
+                "`repr = \"adjacent\"` requires the key of the version's fields, e.g. `content = \"data\"`.",
             )
             .to_compile_error()
             .into();
         }
-        _ => {
+        _ => {}
+    }
+
+    // Validate that integer tags are only used with integer version numbers
+    let int_tags = if let Some((TagType::Int, span)) = args.tag_type {
+        if let Some(version) = versions
+            .iter()
+            .find(|version| version.number.parse::<u64>().is_err())
+        {
             return syn::Error::new(
-                struct_name.span(),
+                version.ident.span(),
                 format!(
-                    "{struct_name}: Versioned derive macro only supports structs, not enums or unions."
-                )
+                    "`tag_type = \"int\"` requires integer version numbers, but `{}` is labeled \"{}\".",
+                    version.ident, version.number
+                ),
+            )
+            .to_compile_error()
+            .into();
+        }
+        if let Repr::Adjacent = args.repr {
+            return syn::Error::new(
+                span,
+                "`tag_type = \"int\"` is only supported with `repr = \"internal\"`.",
             )
             .to_compile_error()
             .into();
         }
+        true
+    } else {
+        false
     };
 
-    // Generate the to_version implementation body
-    let to_version_impl = quote! {
-        #version_enum_name::#latest_version_ident(#latest_version_struct {
-            #(#fields),*
-        })
+    // Validate that the fallback of `unknown_as` is a declared version
+    let unknown_fallback = match &args.unknown_as {
+        Some(number) => {
+            let Some(fallback) = versions
+                .iter()
+                .find(|version| version.number == number.value())
+            else {
+                return syn::Error::new(
+                    number.span(),
+                    format!(
+                        "Version `{}` is not declared. `unknown_as` must name one of the listed versions.",
+                        number.value()
+                    ),
+                )
+                .to_compile_error()
+                .into();
+            };
+            if let Repr::Adjacent = args.repr {
+                return syn::Error::new(
+                    number.span(),
+                    "`unknown_as` is only supported with `repr = \"internal\"`.",
+                )
+                .to_compile_error()
+                .into();
+            }
+            Some(fallback)
+        }
+        None => None,
     };
+    // Serde's derive cannot write integer tags or read an unknown tag as another version, so
+    // the enum implements the serde traits by hand for these options
+    let hand_written_serde = int_tags || unknown_fallback.is_some();
 
-    // Combine everything into the final expanded code
-    let expanded = quote! {
-        #version_enum
+    // Generic parameters of the struct, which the enum and every version struct take as well
+    let generics = &input.generics;
+    if let Some(lifetime) = generics.lifetimes().next() {
+        return syn::Error::new(
+            lifetime.span(),
+            "Lifetime parameters are not supported. Version structs must own their data.",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if let Some((snapshot_struct, _)) = args.snapshots.first()
+        && !generics.params.is_empty()
+    {
+        return syn::Error::new(
+            snapshot_struct.span(),
+            "`snapshot` is not supported for generic structs, since the test cannot choose the type parameters.",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if let Some(sample) = &args.roundtrip_sample
+        && !generics.params.is_empty()
+    {
+        return syn::Error::new(
+            sample.span(),
+            "`test_roundtrip = \"...\"` is not supported for generic structs, since the test cannot choose the type parameters. Use `test_roundtrip` and call `assert_roundtrip` from a test instead.",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    // `version_for` and `as_version` identify version structs by `TypeId`, which requires the
+    // type parameters to be `'static`
+    let mut static_generics = generics.clone();
+    for param in generics.type_params() {
+        let param = &param.ident;
+        static_generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote!(#param: 'static));
+    }
+    let (static_impl_generics, _, static_where_clause) = static_generics.split_for_impl();
+    let struct_ty = quote! { #struct_name #ty_generics };
+    let enum_ty = quote! { #version_enum_name #ty_generics };
+    let version_ty = |version_struct: &syn::Ident| quote! { #version_struct #ty_generics };
 
-        impl serde_versioned::Versioned for #struct_name {
-            type VersionEnum = #version_enum_name;
+    // Generate enum variants for each version (e.g., Version1(UserV1), Version2(UserV2))
+    let version_variants: Vec<_> = versions
+        .iter()
+        .map(|version| {
+            let VersionEntry {
+                number: version_num,
+                ident: version_struct,
+                variant: version_ident,
+                ..
+            } = version;
+            let attrs = &version.attrs;
+            let version_struct = version_ty(version_struct);
+            quote! {
+                #[serde(rename = #version_num)]
+                #(#attrs)*
+                #version_ident(#version_struct)
+            }
+        })
+        .collect();
 
-            fn from_version(version: Self::VersionEnum) -> Result<Self, serde_versioned::VersionConversionError> {
-                match version {
-                    #(#from_version_match_arms)*
+    // Generate the version enum definition
+    // Version structs are required to be `Clone`, so the enum can always derive it
+    // Strategies require `Debug` values, which `Arbitrary` version structs already implement
+    let mut enum_derives: Vec<syn::Path> = vec![syn::parse_quote!(Clone)];
+    if args.derive_serde.serialize() {
+        enum_derives.push(syn::parse_quote!(serde::Serialize));
+    }
+    if args.derive_serde.deserialize() {
+        enum_derives.push(syn::parse_quote!(serde::Deserialize));
+    }
+    if args.proptest {
+        enum_derives.push(syn::parse_quote!(Debug));
+    }
+    // Forward the traits given with `derive(...)`, skipping those already derived by name
+    for path in &args.derives {
+        let derived_name =
+            |path: &syn::Path| path.segments.last().map(|segment| segment.ident.clone());
+        if !enum_derives
+            .iter()
+            .any(|derived| derived_name(derived) == derived_name(path))
+        {
+            enum_derives.push(path.clone());
+        }
+    }
+    let version_tag = args
+        .tag
+        .clone()
+        .unwrap_or_else(|| syn::LitStr::new("version", proc_macro2::Span::call_site()));
+    // Serde names the enum in some error messages, which use the public name even when the
+    // enum is defined under a hidden one
+    let enum_name_str = version_enum_name.to_string();
+    // Serde bounds each type parameter by default, which is ambiguous with a `DeserializeOwned`
+    // bound in the struct's where clause. Bound the version structs instead, which also lets
+    // version structs that borrow from `'static` input deserialize for that lifetime only.
+    let serde_bounds = {
+        let version_types: Vec<_> = versions
+            .iter()
+            .map(|version| version_ty(&version.ident))
+            .collect();
+        let serialize_bound = quote! { #(#version_types: serde::Serialize),* }.to_string();
+        let deserialize_bound = quote! { #(#version_types: serde::Deserialize<'de>),* }.to_string();
+        quote! {
+            #[serde(bound(serialize = #serialize_bound, deserialize = #deserialize_bound))]
+        }
+    };
+    let content = args.content.iter();
+    let version_content = args
+        .content
+        .as_ref()
+        .map_or_else(|| quote! { None }, |content| quote! { Some(#content) });
+    let version_group = args
+        .group
+        .as_ref()
+        .map_or_else(|| quote! { None }, |group| quote! { Some(#group) });
+    // Versions listed in the error for an unknown version
+    let known_versions = versions
+        .iter()
+        .map(|version| format!("`{}`", version.number))
+        .collect::<Vec<_>>()
+        .join(", ");
+    // The hand-written serde traits buffer the version's fields as JSON while reading, to be
+    // read once the tag is known
+    let hand_written_serde_impls = |enum_name: &syn::Ident| {
+        let version_types: Vec<_> = versions
+            .iter()
+            .map(|version| version_ty(&version.ident))
+            .collect();
+        let variants: Vec<_> = versions.iter().map(|version| &version.variant).collect();
+        let numbers: Vec<_> = versions
+            .iter()
+            .map(|version| {
+                if int_tags {
+                    syn::LitInt::new(&format!("{}u64", version.number), version.ident.span())
+                        .into_token_stream()
+                } else {
+                    syn::LitStr::new(&version.number, version.ident.span()).into_token_stream()
                 }
-            }
+            })
+            .collect();
+        let (serialized_tag, deserialized_tag, read_tag) = if int_tags {
+            (
+                quote! { u64 },
+                quote! { serde_versioned::__private::IntTag },
+                quote! { version.0 },
+            )
+        } else {
+            (
+                quote! { &'static str },
+                quote! { serde_versioned::__private::String },
+                quote! { version.as_str() },
+            )
+        };
+        let unknown_version = match unknown_fallback {
+            Some(VersionEntry {
+                number: fallback_num,
+                variant: fallback_variant,
+                ..
+            }) => quote! {
+                serde_versioned::__private::serde_json::from_value(fields)
+                    .map(Self::#fallback_variant)
+                    .map_err(|e| serde::de::Error::custom(format_args!(
+                        "{unknown}; reading it as version {} failed: {e}",
+                        #fallback_num
+                    )))
+            },
+            None => quote! { Err(serde::de::Error::custom(unknown)) },
+        };
+        let mut serialize_generics = generics.clone();
+        let mut deserialize_generics = generics.clone();
+        deserialize_generics
+            .params
+            .insert(0, syn::parse_quote!('de));
+        for version_type in &version_types {
+            serialize_generics
+                .make_where_clause()
+                .predicates
+                .push(syn::parse_quote!(#version_type: serde::Serialize));
+            deserialize_generics
+                .make_where_clause()
+                .predicates
+                .push(syn::parse_quote!(#version_type: serde::de::DeserializeOwned));
+        }
+        let (serialize_impl_generics, _, serialize_where_clause) =
+            serialize_generics.split_for_impl();
+        let (deserialize_impl_generics, _, deserialize_where_clause) =
+            deserialize_generics.split_for_impl();
+        let serialize_impl = args.derive_serde.serialize().then(|| {
+            quote! {
+                impl #serialize_impl_generics serde::Serialize for #enum_name #ty_generics #serialize_where_clause {
+                    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                        #[derive(serde::Serialize)]
+                        struct Tagged<'a, T> {
+                            #[serde(rename = #version_tag)]
+                            version: #serialized_tag,
+                            #[serde(flatten)]
+                            fields: &'a T,
+                        }
 
-            fn to_version(&self) -> Self::VersionEnum {
-                #to_version_impl
+                        match self {
+                            #(Self::#variants(fields) => {
+                                serde::Serialize::serialize(&Tagged { version: #numbers, fields }, serializer)
+                            })*
+                        }
+                    }
+                }
             }
+        });
+        let deserialize_impl = args.derive_serde.deserialize().then(|| {
+            quote! {
+                impl #deserialize_impl_generics serde::Deserialize<'de> for #enum_name #ty_generics #deserialize_where_clause {
+                    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                        #[derive(serde::Deserialize)]
+                        struct Tagged {
+                            #[serde(rename = #version_tag)]
+                            version: #deserialized_tag,
+                            #[serde(flatten)]
+                            fields: serde_versioned::__private::serde_json::Value,
+                        }
 
-            fn extract_version_string(version: &Self::VersionEnum) -> String {
-                match version {
-                    #(#extract_version_match_arms)*
+                        let Tagged { version, fields } = Tagged::deserialize(deserializer)?;
+                        match #read_tag {
+                            #(#numbers => serde_versioned::__private::serde_json::from_value(fields)
+                                .map(Self::#variants)
+                                .map_err(serde::de::Error::custom),)*
+                            version => {
+                                let unknown = serde_versioned::__private::format!(
+                                    "unknown version `{version}`, expected one of {}",
+                                    #known_versions
+                                );
+                                #unknown_version
+                            }
+                        }
+                    }
                 }
             }
+        });
+        quote! {
+            #serialize_impl
+            #deserialize_impl
         }
     };
+    let version_enum_definition = |enum_vis: &syn::Visibility, enum_name: &syn::Ident| {
+        if hand_written_serde {
+            let derives = enum_derives.iter().filter(|path| {
+                !matches!(
+                    quote!(#path).to_string().as_str(),
+                    "serde :: Serialize" | "serde :: Deserialize"
+                )
+            });
+            let variants = versions.iter().map(|version| {
+                let variant = &version.variant;
+                let version_struct = version_ty(&version.ident);
+                let attrs = version
+                    .attrs
+                    .iter()
+                    .filter(|attr| !attr.path().is_ident("serde"));
+                quote! {
+                    #(#attrs)*
+                    #variant(#version_struct)
+                }
+            });
+            let impls = hand_written_serde_impls(enum_name);
+            return quote! {
+                #[derive(#(#derives),*)]
+                #enum_vis enum #enum_name #generics #where_clause {
+                    #(#variants),*
+                }
 
-    TokenStream::from(expanded)
-}
-
-/// Extracts version struct names from the `versioned` attribute.
-///
-/// Parses the `#[versioned(versions = [V1, V2, ...])]` attribute and returns
-/// a vector of tuples containing (`version_number`, `struct_ident`).
-///
-/// # Arguments
-///
-/// * `input` - The derive input containing the struct definition
-///
-/// # Returns
-///
-/// A vector of tuples where each tuple contains:
-/// - A string version number (e.g., "1", "2")
-/// - The identifier of the version struct
-fn extract_versions(input: &DeriveInput) -> Vec<(String, syn::Ident)> {
-    let mut versions = Vec::new();
-
-    // Search for the versioned attribute
-    for attr in &input.attrs {
-        if attr.path().is_ident("versioned")
-            && let Meta::List(meta_list) = &attr.meta
-        {
-            // Parse the format: versioned(versions = [SettingV1, SettingV2])
-            let tokens: TokenStream2 = meta_list.tokens.clone();
-            let result = syn::parse2::<VersionsList>(tokens);
-            if let Ok(versions_list) = result {
-                versions = versions_list.versions;
+                #impls
+            };
+        }
+        if !args.derive_serde.deserialize() {
+            return quote! {
+                #[derive(#(#enum_derives),*)]
+                #[serde(tag = #version_tag, #(content = #content,)* rename = #enum_name_str)]
+                #serde_bounds
+                #enum_vis enum #enum_name #generics #where_clause {
+                    #(#version_variants),*
+                }
+            };
+        }
+        // `Deserialize` is derived for a copy of the enum with serde's `remote`, and implemented
+        // for the enum by reading through `UnknownVersionDeserializer`, so that an unknown
+        // version is reported as such instead of as an unknown variant
+        let derives = enum_derives
+            .iter()
+            .filter(|path| quote!(#path).to_string() != "serde :: Deserialize");
+        let serialize = args.derive_serde.serialize();
+        let serde_attrs = serialize.then(|| {
+            let content = content.clone();
+            quote! {
+                #[serde(tag = #version_tag, #(content = #content,)* rename = #enum_name_str)]
+                #serde_bounds
             }
+        });
+        let variants = versions
+            .iter()
+            .zip(&version_variants)
+            .map(|(version, variant)| {
+                if serialize {
+                    return variant.clone();
+                }
+                let variant_ident = &version.variant;
+                let version_struct = version_ty(&version.ident);
+                let attrs = version
+                    .attrs
+                    .iter()
+                    .filter(|attr| !attr.path().is_ident("serde"));
+                quote! {
+                    #(#attrs)*
+                    #variant_ident(#version_struct)
+                }
+            });
+        let remote = enum_name.to_string();
+        let mut deserialize_generics = generics.clone();
+        deserialize_generics
+            .params
+            .insert(0, syn::parse_quote!('de));
+        for version in versions {
+            let version_type = version_ty(&version.ident);
+            deserialize_generics
+                .make_where_clause()
+                .predicates
+                .push(syn::parse_quote!(#version_type: serde::Deserialize<'de>));
         }
-    }
-
-    versions
-}
+        let (deserialize_impl_generics, _, deserialize_where_clause) =
+            deserialize_generics.split_for_impl();
+        quote! {
+            #[derive(#(#derives),*)]
+            #serde_attrs
+            #enum_vis enum #enum_name #generics #where_clause {
+                #(#variants),*
+            }
 
-/// Structure representing the parsed versions list from the attribute.
-struct VersionsList {
-    /// Vector of (`version_number`, `struct_identifier`) tuples
-    versions: Vec<(String, syn::Ident)>,
-}
+            const _: () = {
+                #[derive(serde::Deserialize)]
+                #[serde(remote = #remote, tag = #version_tag, #(content = #content,)* rename = #enum_name_str)]
+                #serde_bounds
+                #[allow(dead_code)]
+                enum Remote #generics #where_clause {
+                    #(#version_variants),*
+                }
 
-impl syn::parse::Parse for VersionsList {
-    /// Parses the `versions = [...]` syntax from the attribute.
-    ///
-    /// Expected format: `versions = [StructV1, StructV2, ...]`
-    ///
-    /// # Returns
-    ///
-    /// A `VersionsList` containing version numbers (starting from 1) and their corresponding struct identifiers.
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        // Parse the "versions" identifier
-        let ident: syn::Ident = input.parse()?;
-        if ident != "versions" {
-            return Err(syn::Error::new(
-                ident.span(),
-                format!(
-                    "Expected `versions`, found `{ident}`. The correct syntax is: #[versioned(versions = [Version1, Version2, ...])]"
-                ),
-            ));
+                impl #deserialize_impl_generics serde::Deserialize<'de> for #enum_name #ty_generics #deserialize_where_clause {
+                    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                        Remote::deserialize(serde_versioned::__private::UnknownVersionDeserializer::new(
+                            deserializer,
+                            #enum_name_str,
+                            #known_versions,
+                        ))
+                    }
+                }
+            };
         }
+    };
+    let version_enum = if args.existing_enum.is_some() {
+        quote! {}
+    } else if let Some(enum_vis) = &args.enum_visibility {
+        // The enum appears in the public `Versioned` impl, so it must itself be `pub`. Define it
+        // under a hidden name and alias it with the requested visibility instead. A module would
+        // hide it completely, but could not see the version structs of a struct defined inside a
+        // function.
+        let hidden_name =
+            syn::Ident::new(&format!("__{version_enum_name}"), version_enum_name.span());
+        let definition = version_enum_definition(&syn::parse_quote!(pub), &hidden_name);
+        quote! {
+            #[doc(hidden)]
+            #definition
 
-        // Parse the `=` token
-        input.parse::<syn::Token![=]>()?;
-
-        // Parse the array brackets and content
-        let array_content;
-        syn::bracketed!(array_content in input);
+            #[allow(type_alias_bounds)]
+            #enum_vis type #version_enum_name #impl_generics = #hidden_name #ty_generics;
+        }
+    } else {
+        version_enum_definition(vis, &version_enum_name)
+    };
 
-        // Parse comma-separated list of expressions (struct identifiers)
-        let elems = syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated(
-            &array_content,
-        )?;
+    // Versions are checked against `accept` when their match arm is generated below
+    if let Some((_, range_str)) = &args.accept
+        && let Some(version) = versions
+            .iter()
+            .find(|version| version.number.parse::<u64>().is_err())
+    {
+        return syn::Error::new(
+            range_str.span(),
+            format!(
+                "`accept` requires integer version numbers, but `{}` is numbered `{}`.",
+                version.ident, version.number
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
 
-        let mut versions = Vec::new();
-        // Convert each struct identifier to a version number (1-indexed)
-        for (idx, elem) in elems.iter().enumerate() {
-            if let syn::Expr::Path(path) = elem
-                && let Some(ident) = path.path.get_ident()
-            {
-                let version_num = (idx + 1).to_string();
-                versions.push((version_num, ident.clone()));
+    // Match arm rejecting a version without converting it, shared by `from_version` and
+    // `from_version_async` so both apply the same policy
+    let rejected_version_arm = |version: &VersionEntry| {
+        let VersionEntry {
+            number: version_num,
+            ident: version_struct,
+            variant: version_ident,
+            ..
+        } = version;
+        let reject = |message: String| {
+            quote! {
+                #version_enum_name::#version_ident(_) => Err(serde_versioned::VersionConversionError::new(
+                    #version_num,
+                    #message.into(),
+                )),
             }
+        };
+        // Reject versions outside the accepted range
+        if let Some((range, range_str)) = &args.accept
+            && !range_contains(range, version_num)
+        {
+            return Some(reject(format!(
+                "version is outside the accepted range `{}`",
+                range_str.value()
+            )));
+        }
+        // Reject deprecated versions without converting them when they are denied
+        if args.deny_deprecated && args.deprecated.contains(version_struct) {
+            return Some(reject(
+                "version is deprecated and `deny_deprecated` is set".to_string(),
+            ));
         }
-        Ok(Self { versions })
+        // Leave out the conversion of versions that are not readable in this build
+        if let Some(read_only_versions) = &args.read_only_versions
+            && !read_only_versions
+                .iter()
+                .any(|number| number.value() == *version_num)
+        {
+            return Some(reject(
+                "version is not listed in `read_only_versions`".to_string(),
+            ));
+        }
+        None
+    };
+    // Generate match arms for from_version implementation
+    // Each arm converts the version struct and wraps any error in VersionConversionError
+    let from_version_match_arms: Vec<_> = versions
+        .iter()
+        .enumerate()
+        .map(
+            |(
+                index,
+                version @ VersionEntry {
+                    number: version_num,
+                    ident: version_struct,
+                    variant: version_ident,
+                    ..
+                },
+            )| {
+                if let Some(arm) = rejected_version_arm(version) {
+                    return arm;
+                }
+                let deprecated = args.deprecated.contains(version_struct);
+                // Warn when a deprecated field of this version was populated
+                let deprecated_field_checks = args
+                    .deprecated_fields
+                    .iter()
+                    .filter(|(deprecated_struct, _)| deprecated_struct == version_struct)
+                    .map(|(_, field)| {
+                        let struct_name_str = version_struct.to_string();
+                        let field_str = field.to_string();
+                        quote_spanned! {field.span()=>
+                            {
+                                static WARNED: ::core::sync::atomic::AtomicBool = ::core::sync::atomic::AtomicBool::new(false);
+                                #[allow(deprecated)]
+                                let populated = serde_versioned::__private::is_populated(&v.#field);
+                                if populated {
+                                    serde_versioned::__private::warn_deprecated_field(
+                                        &WARNED,
+                                        #struct_name_str,
+                                        #field_str,
+                                        #version_num,
+                                    );
+                                }
+                            }
+                        }
+                    });
+                // Warn each time a deprecated version is read
+                let deprecated_version_warning = deprecated.then(|| {
+                    let struct_name_str = version_struct.to_string();
+                    quote! {
+                        serde_versioned::__private::warn_deprecated_version(#struct_name_str, #version_num);
+                    }
+                });
+                // In chained mode, upgrade one version at a time up to the latest version struct
+                // Each step is spanned at the listed version so a missing step is reported there
+                let chain_steps = if args.chained {
+                    versions[index..]
+                        .windows(2)
+                        .map(|step| {
+                            let (from, to) = (version_ty(&step[0].ident), version_ty(&step[1].ident));
+                            let (from_str, to_str) = (step[0].ident.to_string(), step[1].ident.to_string());
+                            if args.use_from {
+                                quote_spanned! {step[0].ident.span()=>
+                                    let v = <#from as ::core::convert::Into<#to>>::into(v);
+                                }
+                            } else {
+                                quote_spanned! {step[0].ident.span()=>
+                                    let v = <#from as serde_versioned::TryFromVersion<#to>>::try_convert(v)
+                                        .map_err(|e| serde_versioned::__private::add_conversion_context(e, #from_str, #to_str))?;
+                                }
+                            }
+                        })
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                // Name the structs of the failing conversion in its error
+                let last_struct = if args.chained {
+                    &versions[versions.len() - 1].ident
+                } else {
+                    version_struct
+                };
+                let last_struct_str = last_struct.to_string();
+                let struct_name_str = struct_name.to_string();
+                // Spanned at the listed version like the chained steps, so a missing conversion
+                // names the version struct and the current struct at the `versions` list
+                let last_ty = version_ty(last_struct);
+                let conversion = if args.use_from {
+                    quote_spanned! {last_struct.span()=>
+                        Ok(<#last_ty as ::core::convert::Into<#struct_ty>>::into(v))
+                    }
+                } else {
+                    quote_spanned! {last_struct.span()=>
+                        <#last_ty as serde_versioned::TryFromVersion<#struct_ty>>::try_convert(v).map_err(|e| {
+                            serde_versioned::__private::add_conversion_context(e, #last_struct_str, #struct_name_str)
+                        })
+                    }
+                };
+                quote! {
+                    #version_enum_name::#version_ident(v) => {
+                        #deprecated_version_warning
+                        #(#deprecated_field_checks)*
+                        #(#chain_steps)*
+                        #conversion
+                    },
+                }
+            },
+        )
+        .collect();
+
+    // Run the validation hook on the converted value when given
+    let from_version_body = if let Some(validate) = &args.validate {
+        quote! {
+            let current = match version {
+                #(#from_version_match_arms)*
+            }?;
+            #validate(&current)?;
+            Ok(current)
+        }
+    } else {
+        quote! {
+            match version {
+                #(#from_version_match_arms)*
+            }
+        }
+    };
+
+    // Generate match arms for extract_version_string implementation
+    let extract_version_match_arms: Vec<_> = versions
+        .iter()
+        .map(
+            |VersionEntry {
+                 number: version_num,
+                 ident: version_struct,
+                 variant: version_ident,
+                 ..
+             }| {
+                let version_num_lit = syn::LitStr::new(version_num, version_struct.span());
+                quote! {
+                    #version_enum_name::#version_ident(_) => serde_versioned::__private::String::from(#version_num_lit),
+                }
+            },
+        )
+        .collect();
+
+    // Generate match arms for version_tag implementation
+    let version_tag_match_arms: Vec<_> = versions
+        .iter()
+        .map(
+            |VersionEntry {
+                 number: version_num,
+                 variant: version_ident,
+                 ..
+             }| {
+                quote! {
+                    #version_enum_name::#version_ident(_) => #version_num,
+                }
+            },
+        )
+        .collect();
+
+    // Integer tags are serialized as numbers, so `tag_value` returns them as JSON numbers
+    let tag_value_impl = if int_tags {
+        let arms = versions.iter().map(|version| {
+            let variant = &version.variant;
+            let number = syn::LitInt::new(&format!("{}u64", version.number), version.ident.span());
+            quote! {
+                #version_enum_name::#variant(_) => #number,
+            }
+        });
+        quote! {
+            fn tag_value(version: &Self::VersionEnum) -> serde_versioned::__private::serde_json::Value {
+                serde_versioned::__private::serde_json::Value::from(match version {
+                    #(#arms)*
+                })
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
+
+    // Collect the version numbers for matching on version strings
+    let version_nums: Vec<_> = versions.iter().map(|version| &version.number).collect();
+
+    // Assert that each version struct implements the serde traits the enum derives, spanned at
+    // the listed version so a missing derive names the version struct instead of the enum
+    let serde_bounds = match args.derive_serde {
+        SerdeDerive::Serialize => quote! { serde::Serialize },
+        SerdeDerive::Deserialize => quote! { serde::de::DeserializeOwned },
+        SerdeDerive::Both => quote! { serde::Serialize + serde::de::DeserializeOwned },
+    };
+    let serde_assertions = {
+        // Serde bounds each type parameter by the derived traits, so the assertions do as well
+        let mut assertion_generics = generics.clone();
+        for param in generics.type_params() {
+            let param = &param.ident;
+            assertion_generics
+                .make_where_clause()
+                .predicates
+                .push(syn::parse_quote!(#param: #serde_bounds));
+        }
+        // Version structs need only deserialize from `'static` input, so that their fields may
+        // borrow from it (for `from_format_borrowed`)
+        let version_bounds = match args.derive_serde {
+            SerdeDerive::Serialize => quote! { serde::Serialize },
+            SerdeDerive::Deserialize => quote! { serde::Deserialize<'static> },
+            SerdeDerive::Both => quote! { serde::Serialize + serde::Deserialize<'static> },
+        };
+        let (assertion_impl_generics, _, assertion_where_clause) =
+            assertion_generics.split_for_impl();
+        let assertions = versions.iter().map(|version| {
+            let version_struct = version_ty(&version.ident);
+            quote_spanned! {version.ident.span()=>
+                assert_serde::<#version_struct>();
+            }
+        });
+        quote! {
+            const _: () = {
+                fn assert_serde<T: #version_bounds>() {}
+                #[allow(dead_code)]
+                fn assert_versions #assertion_impl_generics () #assertion_where_clause {
+                    #(#assertions)*
+                }
+            };
+        }
+    };
+
+    // Generate VersionMember implementations mapping each version struct to its number, and
+    // `From` implementations wrapping each version struct in its variant unless the enum is the
+    // user's own
+    let version_member_impls: Vec<_> = versions
+        .iter()
+        .map(
+            |VersionEntry {
+                 number: version_num,
+                 ident: version_struct,
+                 variant: version_ident,
+                 ..
+             }| {
+                let version_struct = version_ty(version_struct);
+                let from_impl = args.existing_enum.is_none().then(|| {
+                    quote! {
+                        impl #impl_generics From<#version_struct> for #enum_ty #where_clause {
+                            fn from(version: #version_struct) -> Self {
+                                #version_enum_name::#version_ident(version)
+                            }
+                        }
+                    }
+                });
+                quote! {
+                    impl #impl_generics serde_versioned::VersionMember<#struct_ty> for #version_struct #where_clause {
+                        const VERSION: &'static str = #version_num;
+                    }
+
+                    #from_impl
+                }
+            },
+        )
+        .collect();
+
+    // Generate accessors on the enum borrowing the version struct of each variant, named by the
+    // variant's position like the variants themselves
+    let variant_accessors = versions.iter().enumerate().map(|(index, version)| {
+        let VersionEntry {
+            number: version_num,
+            ident: version_struct,
+            variant: version_ident,
+            ..
+        } = version;
+        let accessor = syn::Ident::new(&format!("as_version_{}", index + 1), version_ident.span());
+        let doc = format!(
+            "Returns the `{version_struct}` held by this enum if it is version {version_num}."
+        );
+        let version_struct = version_ty(version_struct);
+        quote! {
+            #[doc = #doc]
+            #vis fn #accessor(&self) -> Option<&#version_struct> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #version_enum_name::#version_ident(v) => Some(v),
+                    _ => None,
+                }
+            }
+        }
+    });
+    let enum_accessors = if args.existing_enum.is_some() {
+        TokenStream2::new()
+    } else {
+        quote! {
+            impl #impl_generics #enum_ty #where_clause {
+                #(#variant_accessors)*
+            }
+        }
+    };
+
+    // Generate the type lookup for version_for implementation
+    let version_for_checks: Vec<_> = versions
+        .iter()
+        .map(|VersionEntry { ident: version_struct, .. }| {
+            let version_struct = version_ty(version_struct);
+            quote! {
+                if type_id == ::core::any::TypeId::of::<#version_struct>() {
+                    return Some(<#version_struct as serde_versioned::VersionMember<#struct_ty>>::VERSION);
+                }
+            }
+        })
+        .collect();
+
+    // Generate the manifest entry of each version for version_manifest implementation
+    let manifest_entries: Vec<_> = versions
+        .iter()
+        .map(
+            |VersionEntry {
+                 ident: version_struct,
+                 number,
+                 ..
+             }| {
+                let version_struct = version_ty(version_struct);
+                quote! { (#number, ::core::any::type_name::<#version_struct>()) }
+            },
+        )
+        .collect();
+
+    // Generate the downcast of each variant for as_version implementation
+    let as_version_match_arms: Vec<_> = versions
+        .iter()
+        .map(|VersionEntry { variant: version_ident, .. }| {
+            quote! {
+                #version_enum_name::#version_ident(v) => (v as &dyn ::core::any::Any).downcast_ref::<V>(),
+            }
+        })
+        .collect();
+
+    // Extract the fields of the struct with how to access them (`name` or `0`, `1`, ...)
+    let struct_fields: Vec<(syn::Member, &syn::Field)> = match &input.data {
+        Data::Struct(DataStruct {
+            fields: fields @ (Fields::Named(_) | Fields::Unnamed(_)),
+            ..
+        }) => fields.members().zip(fields.iter()).collect(),
+        Data::Struct(DataStruct {
+            fields: Fields::Unit,
+            ..
+        }) => {
+            return syn::Error::new(
+                struct_name.span(),
+                format!(
+                    "{struct_name}: Versioned derive macro does not support unit structs. The struct must have at least one field."
+                )
+            )
+            .to_compile_error()
+            .into();
+        }
+        _ => {
+            return syn::Error::new(
+                struct_name.span(),
+                format!(
+                    "{struct_name}: Versioned derive macro only supports structs, not enums or unions."
+                )
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    let latest_version_ty = version_ty(latest_version_struct);
+
+    // Name each field of the struct in the latest version struct, renamed by `rename_to`, or
+    // `None` for fields left out of it by `skip`
+    let mut version_fields = Vec::new();
+    for (field_name, f) in &struct_fields {
+        let field_args = match extract_field_args(f) {
+            Ok(field_args) => field_args,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        if let syn::Member::Unnamed(_) = field_name
+            && let Some(option) = field_args.option_span
+        {
+            return syn::Error::new(option, "Field options are only supported on named fields.")
+                .to_compile_error()
+                .into();
+        }
+        version_fields.push(if field_args.skip {
+            None
+        } else {
+            Some(
+                field_args
+                    .rename_to
+                    .map_or_else(|| field_name.clone(), syn::Member::Named),
+            )
+        });
+    }
+
+    // Extract field names for cloning into the latest version struct
+    // Tuple struct fields are written as `0: ...`, which struct expressions accept as well
+    let fields: Vec<_> = struct_fields
+        .iter()
+        .zip(&version_fields)
+        .filter_map(|((field_name, f), version_field)| {
+            let version_field = version_field.as_ref()?;
+            // Copy through `Into` so compatible type changes (e.g. `u32` to `u64`) work, and
+            // span it at the field type so an incompatible change is reported on this field
+            Some(quote_spanned! {f.ty.span()=>
+                #version_field: ::core::convert::Into::into(::core::clone::Clone::clone(&self.#field_name))
+            })
+        })
+        .collect();
+
+    // Extract field names for moving between the struct and the latest version struct
+    let moved_fields: Vec<_> = struct_fields
+        .iter()
+        .zip(&version_fields)
+        .filter_map(|((field_name, f), version_field)| {
+            let version_field = version_field.as_ref()?;
+            Some(quote_spanned! {f.ty.span()=>
+                #version_field: ::core::convert::Into::into(self.#field_name)
+            })
+        })
+        .collect();
+    // Skipped fields are not in the latest version struct, so they start from their default
+    let moved_back_fields = |version: TokenStream2| -> Vec<TokenStream2> {
+        struct_fields
+            .iter()
+            .zip(&version_fields)
+            .map(|((field_name, f), version_field)| match version_field {
+                Some(version_field) => quote_spanned! {f.ty.span()=>
+                    #field_name: ::core::convert::Into::into(#version.#version_field)
+                },
+                None => quote_spanned! {f.ty.span()=>
+                    #field_name: ::core::default::Default::default()
+                },
+            })
+            .collect()
+    };
+
+    // Generate the identity FromVersion implementation for the latest version when requested,
+    // or the `From` implementation that `use_from` converts through
+    let identity_impl = if args.identity && args.use_from {
+        let moved_fields = moved_back_fields(quote! { version });
+        quote! {
+            impl #impl_generics ::core::convert::From<#latest_version_ty> for #struct_ty #where_clause {
+                fn from(version: #latest_version_ty) -> Self {
+                    #struct_name {
+                        #(#moved_fields),*
+                    }
+                }
+            }
+        }
+    } else if args.identity {
+        let moved_back_fields = moved_back_fields(quote! { self });
+        quote! {
+            impl #impl_generics serde_versioned::FromVersion<#struct_ty> for #latest_version_ty #where_clause {
+                fn convert(self) -> #struct_ty {
+                    #struct_name {
+                        #(#moved_back_fields),*
+                    }
+                }
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
+
+    // Generate the to_version and into_version bodies, which copy the fields into the latest
+    // version struct unless a `to_latest` function writes it
+    let (to_version_impl, into_version_impl) = if let Some(to_latest) = &args.to_latest {
+        (quote! { #to_latest(self) }, quote! { #to_latest(&self) })
+    } else {
+        (
+            quote! {
+                #version_enum_name::#latest_version_ident(#latest_version_struct {
+                    #(#fields),*
+                })
+            },
+            quote! {
+                #version_enum_name::#latest_version_ident(#latest_version_struct {
+                    #(#moved_fields),*
+                })
+            },
+        )
+    };
+
+    // Generate match arms for to_version_n for the listed downgrades other than the latest
+    let downgrade_versions: Vec<_> = versions
+        .iter()
+        .filter(|version| {
+            version.ident != *latest_version_struct && args.downgrades.contains(&version.ident)
+        })
+        .collect();
+    let to_version_n_match_arms = downgrade_versions.iter().map(|version| {
+        let VersionEntry {
+            number: version_num,
+            ident: version_struct,
+            variant: version_ident,
+            ..
+        } = version;
+        quote! {
+            #version_num => Ok(#version_enum_name::#version_ident(
+                <#version_struct #ty_generics as serde_versioned::IntoVersion<Self>>::downgrade(self),
+            )),
+        }
+    });
+    // Every known version upgrades to the current struct, which can be written as the latest
+    // version or any downgrade
+    let reachable_count = 1 + downgrade_versions.len();
+
+    // Inherent methods generated on the struct, emitted in a single impl block
+    let mut inherent_methods = Vec::new();
+
+    // Generate LATEST_VERSION for comparing against incoming versions
+    inherent_methods.push(quote! {
+        /// The version number written by `to_version`, i.e. `current` or the last one in `versions`.
+        #vis const LATEST_VERSION: &'static str = #latest_version_num;
+    });
+
+    // Generate from_legacy_v1 for records that predate the version enum
+    let first_version_struct = &versions[0].ident;
+    let first_version_ident = &versions[0].variant;
+    inherent_methods.push(quote! {
+        /// Deserializes a legacy record that has no version tag as the first version struct
+        /// and converts it to the current struct.
+        ///
+        /// # Errors
+        ///
+        /// Returns `FormatError::Deserialize` if deserialization fails, or
+        /// `FormatError::VersionConversion` if conversion fails.
+        #vis fn from_legacy_v1<'a, F, E>(input: &'a str, deserializer: F) -> Result<Self, serde_versioned::FormatError<E>>
+        where
+            F: FnOnce(&'a str) -> Result<#first_version_struct #ty_generics, E>,
+            E: ::core::error::Error + Send + Sync + 'static,
+        {
+            deserializer(input)
+                .map_err(|e| serde_versioned::FormatError::deserialize(e, serde_versioned::config::capture_input().then(|| serde_versioned::__private::String::from(input))))
+                .and_then(|v| {
+                    <Self as serde_versioned::Versioned>::from_version(#version_enum_name::#first_version_ident(v))
+                        .map_err(serde_versioned::FormatError::VersionConversion)
+                })
+        }
+    });
+
+    // Generate from_body_and_version for bodies stored apart from their version, which
+    // deserializes the version structs and so is left out with a `Serialize`-only enum
+    let from_body_match_arms = versions.iter().map(
+        |VersionEntry {
+             number: version_num,
+             ident: version_struct,
+             variant: version_ident,
+             ..
+         }| {
+            quote! {
+                #version_num => <#version_struct #ty_generics as serde::Deserialize>::deserialize(body)
+                    .map_err(|e| serde_versioned::FormatError::deserialize(e, None))
+                    .and_then(|v| {
+                        <Self as serde_versioned::Versioned>::from_version(#version_enum_name::#version_ident(v))
+                            .map_err(serde_versioned::FormatError::VersionConversion)
+                    }),
+            }
+        },
+    );
+    let from_body_bounds = versions.iter().map(|version| {
+        let version_struct = &version.ident;
+        quote! { #version_struct #ty_generics: serde::Deserialize<'de>, }
+    });
+    if args.derive_serde.deserialize() {
+        inherent_methods.push(quote! {
+        /// Deserializes an untagged body as the version struct named by a separately stored
+        /// version string and converts it to the current struct.
+        ///
+        /// # Errors
+        ///
+        /// Returns `FormatError::Deserialize` if the version is unknown or deserialization fails,
+        /// or `FormatError::VersionConversion` if conversion fails.
+        #vis fn from_body_and_version<'de, D>(version: &str, body: D) -> Result<Self, serde_versioned::FormatError<D::Error>>
+        where
+            D: serde::Deserializer<'de>,
+            D::Error: ::core::error::Error + Send + Sync + 'static,
+            #(#from_body_bounds)*
+        {
+            match version {
+                #(#from_body_match_arms)*
+                _ => Err(serde_versioned::FormatError::deserialize(
+                    <D::Error as serde::de::Error>::custom(::core::format_args!("unknown version `{version}`")),
+                    None,
+                )),
+            }
+        }
+        });
+    }
+
+    // Generate discriminant for compact binary tags, from the version numbers when they are all
+    // integers and from the positions in `versions` otherwise
+    let numeric_versions: Option<Vec<u32>> = versions
+        .iter()
+        .map(|version| version.number.parse().ok())
+        .collect();
+    let discriminant_match_arms = versions.iter().enumerate().map(|(index, version)| {
+        let variant = &version.variant;
+        let discriminant = numeric_versions.as_ref().map_or_else(
+            || u32::try_from(index + 1).expect("too many versions"),
+            |numbers| numbers[index],
+        );
+        quote! {
+            #version_enum_name::#variant(_) => #discriminant,
+        }
+    });
+    inherent_methods.push(quote! {
+        /// Returns a stable numeric tag for the version held by `version`, for compact binary
+        /// encodings.
+        ///
+        /// This is the integer version number, or the 1-based position in `versions` when the
+        /// version numbers are not all integers.
+        #vis fn discriminant(version: &#enum_ty) -> u32 {
+            match version {
+                #(#discriminant_match_arms)*
+            }
+        }
+    });
+
+    // Generate into_latest for reading the latest version without conversion
+    let into_latest_arm = if versions.len() > 1 {
+        quote! { _ => None, }
+    } else {
+        TokenStream2::new()
+    };
+    inherent_methods.push(quote! {
+        /// Returns the latest version struct if `version` holds the latest version, without
+        /// converting it to the current struct, or `None` for older versions.
+        #vis fn into_latest(version: #enum_ty) -> Option<#latest_version_ty> {
+            match version {
+                #version_enum_name::#latest_version_ident(v) => Some(v),
+                #into_latest_arm
+            }
+        }
+    });
+
+    // Generate from_latest for building the current struct from the latest version struct
+    // The bound is higher-ranked so that it is only checked where the method is called, since
+    // the latest version struct may implement `TryFromVersion` instead of `FromVersion`
+    let (latest_conversion_bound, latest_conversion) = if args.use_from {
+        (
+            quote! { ::core::convert::Into<Self> },
+            quote! { ::core::convert::Into::into(version) },
+        )
+    } else {
+        (
+            quote! { serde_versioned::FromVersion<Self> },
+            quote! { serde_versioned::FromVersion::convert(version) },
+        )
+    };
+    inherent_methods.push(quote! {
+        /// Converts the latest version struct to the current struct, without going through
+        /// the version enum.
+        #vis fn from_latest(version: #latest_version_ty) -> Self
+        where
+            for<'a> #latest_version_ty: #latest_conversion_bound,
+        {
+            #latest_conversion
+        }
+    });
+
+    // Generate from_version_async when the async option is given
+    if args.async_conversion {
+        let from_version_async_match_arms: Vec<_> = versions
+            .iter()
+            .map(
+                |version @ VersionEntry {
+                     number: version_num,
+                     ident: version_struct,
+                     variant: version_ident,
+                     ..
+                 }| {
+                    if let Some(arm) = rejected_version_arm(version) {
+                        return arm;
+                    }
+                    let version_struct_str = version_struct.to_string();
+                    let struct_name_str = struct_name.to_string();
+                    let deprecated_version_warning =
+                        args.deprecated.contains(version_struct).then(|| {
+                            quote! {
+                                serde_versioned::__private::warn_deprecated_version(#version_struct_str, #version_num);
+                            }
+                        });
+                    quote! {
+                        #version_enum_name::#version_ident(v) => {
+                            #deprecated_version_warning
+                            <#version_struct as serde_versioned::AsyncFromVersion<Self>>::convert_async(v).await.map_err(|e| {
+                                serde_versioned::__private::add_conversion_context(e, #version_struct_str, #struct_name_str)
+                            })
+                        },
+                    }
+                },
+            )
+            .collect();
+        // Check the converted value with the validation hook, as `from_version` does
+        let validate_async = args.validate.as_ref().map(|validate| {
+            quote! {
+                let result = result.and_then(|current| {
+                    #validate(&current)?;
+                    Ok(current)
+                });
+            }
+        });
+
+        inherent_methods.push(quote! {
+            /// Asynchronously converts a versioned enum instance to the current struct
+            /// using each version's `AsyncFromVersion` implementation.
+            ///
+            /// The returned future holds only the version being converted and its conversion's
+            /// future, so it is `Send` whenever those are and can be spawned on a multi-threaded
+            /// runtime.
+            #vis async fn from_version_async(version: #enum_ty) -> Result<Self, serde_versioned::VersionConversionError> {
+                let result = match version {
+                    #(#from_version_async_match_arms)*
+                };
+                #validate_async
+                result.map_err(|e| {
+                    serde_versioned::__private::index_conversion_error(e, <Self as serde_versioned::Versioned>::all_versions())
+                })
+            }
+        });
+    }
+
+    // Generate from_version_with when a context type is given
+    if let Some(context) = &args.context {
+        let from_version_with_match_arms = versions.iter().map(|version| {
+            let version_ident = &version.variant;
+            quote! {
+                #version_enum_name::#version_ident(v) => {
+                    serde_versioned::FromVersionWith::convert_with(v, ctx)
+                },
+            }
+        });
+
+        inherent_methods.push(quote! {
+            /// Converts a versioned enum instance to the current struct using each version's
+            /// `FromVersionWith` implementation with the given context.
+            #vis fn from_version_with(version: #enum_ty, ctx: &#context) -> Self {
+                match version {
+                    #(#from_version_with_match_arms)*
+                }
+            }
+        });
+    }
+
+    // Generate value_parser when the clap option is given
+    if args.clap {
+        let version_nums = versions.iter().map(|version| &version.number);
+        inherent_methods.push(quote! {
+            /// Returns a `clap` value parser accepting the declared version numbers.
+            #vis fn value_parser() -> serde_versioned::__private::clap::builder::PossibleValuesParser {
+                serde_versioned::__private::clap::builder::PossibleValuesParser::new([#(#version_nums),*])
+            }
+        });
+    }
+
+    // Generate group_name when the struct belongs to a group
+    if let Some(group) = &args.group {
+        inherent_methods.push(quote! {
+            /// The group of structs whose versions move in lockstep with this one (`group`).
+            #vis fn group_name() -> &'static str {
+                #group
+            }
+        });
+    }
+
+    // Generate version_strategy and the enum's `Arbitrary` implementation when the proptest
+    // option is given
+    let arbitrary_impl = if args.proptest {
+        let strategies = versions.iter().map(
+            |VersionEntry {
+                 ident: version_struct,
+                 variant: version_ident,
+                 ..
+             }| {
+                quote! {
+                    serde_versioned::__private::proptest::strategy::Strategy::boxed(
+                        serde_versioned::__private::proptest::strategy::Strategy::prop_map(
+                            serde_versioned::__private::proptest::arbitrary::any::<#version_struct #ty_generics>(),
+                            #version_enum_name::#version_ident,
+                        ),
+                    )
+                }
+            },
+        );
+        inherent_methods.push(quote! {
+            /// Returns a `proptest` strategy producing arbitrary values of every version.
+            #vis fn version_strategy() -> impl serde_versioned::__private::proptest::strategy::Strategy<Value = #enum_ty> {
+                serde_versioned::__private::proptest::arbitrary::any::<#enum_ty>()
+            }
+        });
+        // Strategies are `'static`, so the type parameters must be as well
+        let mut arbitrary_generics = static_generics.clone();
+        let where_clause = arbitrary_generics.make_where_clause();
+        for version in versions {
+            let version_struct = version_ty(&version.ident);
+            where_clause.predicates.push(syn::parse_quote!(
+                #version_struct: serde_versioned::__private::proptest::arbitrary::Arbitrary
+            ));
+        }
+        let (arbitrary_impl_generics, _, arbitrary_where_clause) =
+            arbitrary_generics.split_for_impl();
+        quote! {
+            impl #arbitrary_impl_generics serde_versioned::__private::proptest::arbitrary::Arbitrary for #enum_ty #arbitrary_where_clause {
+                type Parameters = ();
+                type Strategy = serde_versioned::__private::proptest::strategy::BoxedStrategy<Self>;
+
+                fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                    serde_versioned::__private::proptest::strategy::Strategy::boxed(
+                        serde_versioned::__private::proptest::strategy::Union::new([#(#strategies),*]),
+                    )
+                }
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
+
+    // Generate all_samples when the samples option is given
+    if args.samples {
+        let samples = versions.iter().map(
+            |VersionEntry {
+                 number: version_num,
+                 ident: version_struct,
+                 variant: version_ident,
+                 ..
+             }| {
+                quote_spanned! {version_struct.span()=>
+                    (
+                        #version_num,
+                        <Self as serde_versioned::Versioned>::from_version(#version_enum_name::#version_ident(
+                            <#version_struct #ty_generics as Default>::default(),
+                        ))
+                        .unwrap_or_else(|e| panic!("sample of version {} does not convert: {e}", #version_num)),
+                    )
+                }
+            },
+        );
+        inherent_methods.push(quote! {
+            /// Returns the default value of every version converted to the current struct, along
+            /// with its version number, in declaration order.
+            ///
+            /// # Panics
+            ///
+            /// Panics if the default value of a version fails to convert.
+            #vis fn all_samples() -> serde_versioned::__private::Vec<(&'static str, Self)> {
+                serde_versioned::__private::vec![#(#samples),*]
+            }
+        });
+    }
+
+    // Generate the JSON roundtrip helper, and its test when a sample is given
+    if args.test_roundtrip.is_some() {
+        inherent_methods.push(quote! {
+            /// Writes `value` as JSON, reads it back and asserts that it is unchanged.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `value` cannot be written or read back, or reads back as a different value.
+            #[cfg(test)]
+            #[track_caller]
+            #vis fn assert_roundtrip(value: &Self)
+            where
+                Self: ::core::cmp::PartialEq + ::core::fmt::Debug,
+                <Self as serde_versioned::Versioned>::VersionEnum:
+                    serde::Serialize + serde::de::DeserializeOwned,
+            {
+                let json = match <Self as serde_versioned::Versioned>::to_json(value) {
+                    Ok(json) => json,
+                    Err(e) => panic!("`{}` cannot be written as JSON: {e}", stringify!(#struct_name)),
+                };
+                match <Self as serde_versioned::Versioned>::from_json(&json) {
+                    Ok(read) => assert_eq!(
+                        &read,
+                        value,
+                        "`{}` changed after a roundtrip through {}",
+                        stringify!(#struct_name),
+                        json,
+                    ),
+                    Err(e) => panic!("`{}` cannot be read back from {json}: {e}", stringify!(#struct_name)),
+                }
+            }
+        });
+    }
+    let roundtrip_test = if let Some(sample) = &args.roundtrip_sample {
+        let test_name = syn::Ident::new(
+            &format!("__serde_versioned_roundtrip_{struct_name}"),
+            struct_name.span(),
+        );
+        quote! {
+            #[cfg(test)]
+            #[test]
+            #[allow(non_snake_case, unnameable_test_items)]
+            fn #test_name() {
+                #struct_name::assert_roundtrip(&#sample());
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
+
+    // Generate the wire format snapshot test when snapshots are given
+    let snapshot_test = if args.snapshots.is_empty() {
+        TokenStream2::new()
+    } else {
+        let test_name = syn::Ident::new(
+            &format!("__serde_versioned_snapshot_{struct_name}"),
+            struct_name.span(),
+        );
+        let assertions = args.snapshots.iter().map(|(snapshot_struct, expected)| {
+            let version = versions
+                .iter()
+                .find(|version| version.ident == *snapshot_struct)
+                .expect("snapshot versions are validated above");
+            let version_ident = &version.variant;
+            quote_spanned! {expected.span()=>
+                assert_eq!(
+                    serde_versioned::__private::serde_json::to_string(
+                        &#version_enum_name::#version_ident(<#snapshot_struct as ::core::default::Default>::default())
+                    )
+                    .unwrap(),
+                    #expected,
+                    "the wire format of `{}` changed",
+                    stringify!(#snapshot_struct),
+                );
+            }
+        });
+        // A test inside a function body is not run, which only warns
+        quote! {
+            #[cfg(test)]
+            #[test]
+            #[allow(non_snake_case, unnameable_test_items)]
+            fn #test_name() {
+                #(#assertions)*
+            }
+        }
+    };
+
+    // Order versions with the custom comparator and test that the declared order agrees with it
+    let (compare_versions_impl, version_order_test) = if let Some(parser) = &args.version_parser {
+        let test_name = syn::Ident::new(
+            &format!("__serde_versioned_version_order_{struct_name}"),
+            struct_name.span(),
+        );
+        let version_nums = versions.iter().map(|version| &version.number);
+        (
+            quote! {
+                fn compare_versions(a: &str, b: &str) -> Option<::core::cmp::Ordering> {
+                    Some(#parser(a, b))
+                }
+            },
+            quote! {
+                #[cfg(test)]
+                #[test]
+                #[allow(non_snake_case, unnameable_test_items)]
+                fn #test_name() {
+                    let versions: &[&str] = &[#(#version_nums),*];
+                    for pair in versions.windows(2) {
+                        assert_eq!(
+                            #parser(pair[0], pair[1]),
+                            ::core::cmp::Ordering::Less,
+                            "version {} is listed before version {}, but `{}` does not order it first",
+                            pair[0],
+                            pair[1],
+                            stringify!(#parser),
+                        );
+                    }
+                }
+            },
+        )
+    } else {
+        (TokenStream2::new(), TokenStream2::new())
+    };
+
+    let struct_name_str = struct_name.to_string();
+
+    // Combine everything into the final expanded code
+    let expanded = quote! {
+        #version_enum
+
+        impl #static_impl_generics serde_versioned::Versioned for #struct_ty #static_where_clause {
+            type VersionEnum = #enum_ty;
+
+            const VERSION_TAG: &'static str = #version_tag;
+
+            const VERSION_CONTENT: Option<&'static str> = #version_content;
+
+            const VERSION_GROUP: Option<&'static str> = #version_group;
+
+            fn from_version(version: Self::VersionEnum) -> Result<Self, serde_versioned::VersionConversionError> {
+                let read_version = <Self as serde_versioned::Versioned>::version_tag(&version);
+                // Run the conversion in a closure so that early returns are traced as well
+                let result = (move || -> Result<Self, serde_versioned::VersionConversionError> {
+                    #from_version_body
+                })()
+                .map_err(|e| {
+                    serde_versioned::__private::index_conversion_error(e, <Self as serde_versioned::Versioned>::all_versions())
+                });
+                serde_versioned::__private::trace_conversion(read_version, #struct_name_str, &result);
+                result
+            }
+
+            fn to_version(&self) -> Self::VersionEnum {
+                #to_version_impl
+            }
+
+            fn into_version(self) -> Self::VersionEnum {
+                #into_version_impl
+            }
+
+            fn extract_version_string(version: &Self::VersionEnum) -> serde_versioned::__private::String {
+                match version {
+                    #(#extract_version_match_arms)*
+                }
+            }
+
+            fn known_version_tag(version: &Self::VersionEnum) -> Option<&'static str> {
+                Some(match version {
+                    #(#version_tag_match_arms)*
+                })
+            }
+
+            #tag_value_impl
+
+            fn to_version_n(&self, version: &str) -> Result<Self::VersionEnum, serde_versioned::VersionConversionError> {
+                match version {
+                    #latest_version_num => Ok(serde_versioned::Versioned::to_version(self)),
+                    #(#to_version_n_match_arms)*
+                    _ => Err(serde_versioned::VersionConversionError::new(
+                        version,
+                        "cannot write the current struct in this version".into(),
+                    )),
+                }
+            }
+
+            fn version_count_reachable_from(version: &str) -> usize {
+                match version {
+                    #(#version_nums)|* => #reachable_count,
+                    _ => 0,
+                }
+            }
+
+            fn all_versions() -> &'static [&'static str] {
+                &[#(#version_nums),*]
+            }
+
+            #compare_versions_impl
+
+            fn version_for<V: 'static>() -> Option<&'static str> {
+                let type_id = ::core::any::TypeId::of::<V>();
+                #(#version_for_checks)*
+                None
+            }
+
+            fn as_version<V: 'static>(version: &Self::VersionEnum) -> Option<&V> {
+                match version {
+                    #(#as_version_match_arms)*
+                }
+            }
+
+            fn version_manifest() -> serde_versioned::VersionManifest {
+                serde_versioned::VersionManifest {
+                    tag: #version_tag,
+                    versions: serde_versioned::__private::vec![#(#manifest_entries),*],
+                }
+            }
+        }
+
+        #serde_assertions
+
+        #(#version_member_impls)*
+
+        #enum_accessors
+
+        #identity_impl
+
+        #arbitrary_impl
+
+        #snapshot_test
+
+        #roundtrip_test
+
+        #version_order_test
+
+        impl #static_impl_generics #struct_ty #static_where_clause {
+            #(#inherent_methods)*
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// Extracts the options from the `versioned` attribute.
+///
+/// Parses the `#[versioned(versions = [V1, V2, ...], ...)]` attribute into a
+/// [`VersionedArgs`]. Options from multiple `versioned` attributes are combined.
+///
+/// # Arguments
+///
+/// * `input` - The derive input containing the struct definition
+///
+/// # Errors
+///
+/// Returns an error if the attribute contains an unknown option or malformed syntax.
+fn extract_args(input: &DeriveInput) -> syn::Result<VersionedArgs> {
+    let mut args = VersionedArgs::default();
+
+    // Search for the versioned attribute
+    for attr in &input.attrs {
+        if attr.path().is_ident("versioned") {
+            attr.parse_args_with(|input: syn::parse::ParseStream| args.parse_options(input))?;
+        }
+    }
+
+    Ok(args)
+}
+
+/// Extracts the options of the `versioned` attribute on a field of the current struct.
+///
+/// # Errors
+///
+/// Returns an error if the attribute contains an unknown option or malformed syntax.
+fn extract_field_args(field: &syn::Field) -> syn::Result<FieldArgs> {
+    let mut args = FieldArgs::default();
+    for attr in &field.attrs {
+        if attr.path().is_ident("versioned") {
+            attr.parse_args_with(|input: syn::parse::ParseStream| args.parse_options(input))?;
+        }
+    }
+    Ok(args)
+}
+
+/// Options of the `versioned` attribute on a field of the current struct.
+#[derive(Default)]
+struct FieldArgs {
+    /// Name of the field in the latest version struct (`rename_to = "..."`)
+    rename_to: Option<syn::Ident>,
+    /// Whether the field is left out of the latest version struct (`skip`)
+    skip: bool,
+    /// Span of the first option given, for reporting options on unsupported fields
+    option_span: Option<proc_macro2::Span>,
+}
+
+impl FieldArgs {
+    /// Parses a comma-separated list of field options into `self`.
+    ///
+    /// Supported options:
+    /// - `rename_to = "field"`
+    /// - `skip`
+    fn parse_options(&mut self, input: syn::parse::ParseStream) -> syn::Result<()> {
+        while !input.is_empty() {
+            let ident: syn::Ident = input.parse()?;
+            self.option_span.get_or_insert(ident.span());
+            match ident.to_string().as_str() {
+                "skip" => self.skip = true,
+                "rename_to" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let lit: syn::LitStr = input.parse()?;
+                    self.rename_to = Some(lit.parse()?);
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!(
+                            "Unknown field option `{ident}`. The correct syntax is: #[versioned(rename_to = \"field\")]"
+                        ),
+                    ));
+                }
+            }
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<syn::Token![,]>()?;
+        }
+        Ok(())
+    }
+}
+
+/// Structure representing the parsed options from the `versioned` attribute.
+#[derive(Default)]
+struct VersionedArgs {
+    /// The listed versions, in declaration order
+    versions: Vec<VersionEntry>,
+    /// Whether to generate `from_version_async` (`async`)
+    async_conversion: bool,
+    /// Whether versions convert through `Into` instead of `FromVersion` (`use_from`)
+    use_from: bool,
+    /// Context type passed to `from_version_with` (`context = "..."`)
+    context: Option<syn::Type>,
+    /// Key of the version tag, overriding `"version"` (`tag = "..."`)
+    tag: Option<syn::LitStr>,
+    /// Name of the generated enum, overriding `{Struct}Version` (`enum_name = "..."`)
+    enum_name: Option<syn::Ident>,
+    /// Existing enum to implement `Versioned` against instead of generating one (`enum = ...`)
+    existing_enum: Option<syn::Ident>,
+    /// Visibility of the generated enum, overriding the struct's (`visibility = "..."`)
+    enum_visibility: Option<syn::Visibility>,
+    /// Whether to generate `FromVersion` for the latest version by moving fields (`identity`)
+    identity: bool,
+    /// Span of the option generating the JSON roundtrip helper (`test_roundtrip`)
+    test_roundtrip: Option<proc_macro2::Span>,
+    /// Function returning the value the generated roundtrip test checks (`test_roundtrip = "..."`)
+    roundtrip_sample: Option<syn::ExprPath>,
+    /// Deprecated (`version_struct`, `field`) pairs to warn about on read (`deprecated_fields = [...]`)
+    deprecated_fields: Vec<(syn::Ident, syn::Ident)>,
+    /// Expected JSON of each version struct's default value (`snapshot(...)`)
+    snapshots: Vec<(syn::Ident, syn::LitStr)>,
+    /// Whether to generate a `clap` value parser over the versions (`clap`)
+    clap: bool,
+    /// Range of accepted version numbers and its source text (`accept = "..."`)
+    accept: Option<(syn::ExprRange, syn::LitStr)>,
+    /// Whether to upgrade through each adjacent version instead of directly (`chained`)
+    chained: bool,
+    /// Version structs `to_version_n` can downgrade to (`downgrades = [...]`)
+    downgrades: Vec<syn::Ident>,
+    /// Whether to generate a `proptest` strategy over the versions (`proptest`)
+    proptest: bool,
+    /// Whether to generate `all_samples` from each version's default value (`samples`)
+    samples: bool,
+    /// Version numbers converted by `from_version`, or all if unset (`read_only_versions = [...]`)
+    read_only_versions: Option<Vec<syn::LitStr>>,
+    /// Serde traits derived on the enum (`derive_serde = "..."`)
+    derive_serde: SerdeDerive,
+    /// Function checking the converted value in `from_version` (`validate = "..."`)
+    validate: Option<syn::ExprPath>,
+    /// Serde representation of the enum (`repr = "..."`)
+    repr: Repr,
+    /// Key of the version's fields in the adjacent representation (`content = "..."`)
+    content: Option<syn::LitStr>,
+    /// Comparator ordering the version numbers (`version_parser = "..."`)
+    version_parser: Option<syn::ExprPath>,
+    /// Additional traits derived on the enum (`derive(...)`)
+    derives: Vec<syn::Path>,
+    /// Function writing the current struct as the latest version (`to_latest = "..."`)
+    to_latest: Option<syn::ExprPath>,
+    /// Version struct the current struct is written as, if not the last one (`current = ...`)
+    current: Option<syn::Ident>,
+    /// Group of structs versioned in lockstep (`group = "..."`)
+    group: Option<syn::LitStr>,
+    /// Type of the serialized version tag (`tag_type = "..."`), with the option's span
+    tag_type: Option<(TagType, proc_macro2::Span)>,
+    /// Version read in place of an unknown version (`unknown_as = "..."`)
+    unknown_as: Option<syn::LitStr>,
+    /// Version structs that warn when read (`deprecated = [...]`)
+    deprecated: Vec<syn::Ident>,
+    /// Whether reading a deprecated version is an error (`deny_deprecated`)
+    deny_deprecated: bool,
+}
+
+/// Serde representation of the generated enum, selected by `repr`.
+#[derive(Default, Clone, Copy)]
+enum Repr {
+    /// The tag sits beside the version's fields (`#[serde(tag = ...)]`)
+    #[default]
+    Internal,
+    /// The version's fields sit under a separate key (`#[serde(tag = ..., content = ...)]`)
+    Adjacent,
+}
+
+/// Type of the serialized version tag, selected by `tag_type`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TagType {
+    /// The tag is the version number as a string (`"2"`)
+    String,
+    /// The tag is the version number as an integer (`2`)
+    Int,
+}
+
+/// Serde traits derived on the generated enum, selected by `derive_serde`.
+#[derive(Default, Clone, Copy)]
+enum SerdeDerive {
+    Serialize,
+    Deserialize,
+    #[default]
+    Both,
+}
+
+impl SerdeDerive {
+    /// Whether the enum derives `Serialize`.
+    fn serialize(self) -> bool {
+        matches!(self, SerdeDerive::Serialize | SerdeDerive::Both)
+    }
+
+    /// Whether the enum derives `Deserialize`.
+    fn deserialize(self) -> bool {
+        matches!(self, SerdeDerive::Deserialize | SerdeDerive::Both)
+    }
+}
+
+impl VersionedArgs {
+    /// Parses a comma-separated list of options into `self`.
+    ///
+    /// Supported options:
+    /// - `versions = [StructV1, StructV2, ...]`
+    /// - `async`
+    /// - `tag = "v"`
+    /// - `visibility = "pub(crate)"`
+    /// - `enum_name = "StructSchema"`
+    /// - `enum = StructVersion`
+    /// - `identity`
+    /// - `deprecated_fields = [StructV1::field, ...]`
+    /// - `snapshot(StructV1 = "...", ...)`
+    /// - `clap`
+    /// - `accept = "1..=2"`
+    /// - `chained`
+    /// - `downgrades = [StructV1, ...]`
+    /// - `proptest`
+    /// - `samples`
+    /// - `test_roundtrip` or `test_roundtrip = "Struct::sample"`
+    /// - `read_only_versions = ["1", ...]`
+    /// - `derive_serde = "serialize"`
+    /// - `validate = "Struct::validate"`
+    /// - `repr = "adjacent"`
+    /// - `content = "data"`
+    /// - `version_parser = "compare_versions"`
+    /// - `derive(Debug, PartialEq, ...)`
+    /// - `to_latest = "Struct::to_latest"`
+    /// - `current = StructV2`
+    /// - `group = "schema"`
+    /// - `use_from`
+    /// - `context = "MigrationContext"`
+    /// - `tag_type = "int"`
+    /// - `unknown_as = "2"`
+    /// - `deprecated = [StructV1, ...]`
+    /// - `deny_deprecated`
+    fn parse_options(&mut self, input: syn::parse::ParseStream) -> syn::Result<()> {
+        while !input.is_empty() {
+            // `async` and `enum` are keywords, so parse the option name with `parse_any`
+            let ident = input.call(syn::Ident::parse_any)?;
+            match ident.to_string().as_str() {
+                "versions" => {
+                    input.parse::<syn::Token![=]>()?;
+                    self.versions = parse_versions_list(input)?;
+                }
+                "async" => self.async_conversion = true,
+                "use_from" => self.use_from = true,
+                "identity" => self.identity = true,
+                "clap" => self.clap = true,
+                "proptest" => self.proptest = true,
+                "samples" => self.samples = true,
+                "test_roundtrip" => {
+                    self.test_roundtrip = Some(ident.span());
+                    if input.peek(syn::Token![=]) {
+                        input.parse::<syn::Token![=]>()?;
+                        let lit: syn::LitStr = input.parse()?;
+                        self.roundtrip_sample = Some(lit.parse()?);
+                    }
+                }
+                "chained" => self.chained = true,
+                "deny_deprecated" => self.deny_deprecated = true,
+                "accept" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let lit: syn::LitStr = input.parse()?;
+                    self.accept = Some((parse_version_range(&lit)?, lit));
+                }
+                "downgrades" => {
+                    input.parse::<syn::Token![=]>()?;
+                    self.downgrades = parse_idents(input)?;
+                }
+                "deprecated" => {
+                    input.parse::<syn::Token![=]>()?;
+                    self.deprecated = parse_idents(input)?;
+                }
+                "deprecated_fields" => {
+                    input.parse::<syn::Token![=]>()?;
+                    self.deprecated_fields = parse_deprecated_fields(input)?;
+                }
+                "snapshot" => self.snapshots = parse_snapshots(input)?,
+                "derive" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    self.derives.extend(
+                        content.parse_terminated(syn::Path::parse_mod_style, syn::Token![,])?,
+                    );
+                }
+                "read_only_versions" => {
+                    input.parse::<syn::Token![=]>()?;
+                    self.read_only_versions = Some(parse_version_numbers(input)?);
+                }
+                "tag" => {
+                    input.parse::<syn::Token![=]>()?;
+                    self.tag = Some(input.parse()?);
+                }
+                "derive_serde" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let lit: syn::LitStr = input.parse()?;
+                    self.derive_serde = match lit.value().as_str() {
+                        "serialize" => SerdeDerive::Serialize,
+                        "deserialize" => SerdeDerive::Deserialize,
+                        "both" => SerdeDerive::Both,
+                        other => {
+                            return Err(syn::Error::new(
+                                lit.span(),
+                                format!(
+                                    "Unknown `derive_serde` value `{other}`. Expected \"serialize\", \"deserialize\", or \"both\"."
+                                ),
+                            ));
+                        }
+                    };
+                }
+                "repr" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let lit: syn::LitStr = input.parse()?;
+                    self.repr = match lit.value().as_str() {
+                        "internal" => Repr::Internal,
+                        "adjacent" => Repr::Adjacent,
+                        other => {
+                            return Err(syn::Error::new(
+                                lit.span(),
+                                format!(
+                                    "Unknown `repr` value `{other}`. Expected \"internal\" or \"adjacent\"."
+                                ),
+                            ));
+                        }
+                    };
+                }
+                "tag_type" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let lit: syn::LitStr = input.parse()?;
+                    let tag_type = match lit.value().as_str() {
+                        "string" => TagType::String,
+                        "int" => TagType::Int,
+                        other => {
+                            return Err(syn::Error::new(
+                                lit.span(),
+                                format!(
+                                    "Unknown `tag_type` value `{other}`. Expected \"string\" or \"int\"."
+                                ),
+                            ));
+                        }
+                    };
+                    self.tag_type = Some((tag_type, lit.span()));
+                }
+                "version_parser" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let lit: syn::LitStr = input.parse()?;
+                    self.version_parser = Some(lit.parse()?);
+                }
+                "content" => {
+                    input.parse::<syn::Token![=]>()?;
+                    self.content = Some(input.parse()?);
+                }
+                "unknown_as" => {
+                    input.parse::<syn::Token![=]>()?;
+                    self.unknown_as = Some(input.parse()?);
+                }
+                "context" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let lit: syn::LitStr = input.parse()?;
+                    self.context = Some(lit.parse()?);
+                }
+                "validate" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let lit: syn::LitStr = input.parse()?;
+                    self.validate = Some(lit.parse()?);
+                }
+                "to_latest" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let lit: syn::LitStr = input.parse()?;
+                    self.to_latest = Some(lit.parse()?);
+                }
+                "current" => {
+                    input.parse::<syn::Token![=]>()?;
+                    self.current = Some(input.parse()?);
+                }
+                "group" => {
+                    input.parse::<syn::Token![=]>()?;
+                    self.group = Some(input.parse()?);
+                }
+                "enum_name" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let lit: syn::LitStr = input.parse()?;
+                    self.enum_name = Some(lit.parse()?);
+                }
+                "enum" => {
+                    input.parse::<syn::Token![=]>()?;
+                    self.existing_enum = Some(input.parse()?);
+                }
+                "visibility" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let lit: syn::LitStr = input.parse()?;
+                    self.enum_visibility = Some(lit.parse()?);
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!(
+                            "Unknown option `{ident}`. The correct syntax is: #[versioned(versions = [Version1, Version2, ...])]"
+                        ),
+                    ));
+                }
+            }
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<syn::Token![,]>()?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the string of the `accept = "..."` option as a range of version numbers.
+///
+/// Expected format: `"1..=2"`, `"2.."`, `"..3"`, or another integer range
+///
+/// # Returns
+///
+/// The range expression, with each bound an integer literal.
+fn parse_version_range(lit: &syn::LitStr) -> syn::Result<syn::ExprRange> {
+    let range: syn::ExprRange = lit.parse().map_err(|_| {
+        syn::Error::new(
+            lit.span(),
+            "Expected a range of version numbers, e.g. `accept = \"1..=2\"`",
+        )
+    })?;
+    for bound in [&range.start, &range.end].into_iter().flatten() {
+        if !matches!(
+            &**bound,
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(_),
+                ..
+            })
+        ) {
+            return Err(syn::Error::new(
+                lit.span(),
+                "Range bounds in `accept` must be integer version numbers",
+            ));
+        }
+    }
+    Ok(range)
+}
+
+/// Returns whether the version numbered `number` is in a range parsed by [`parse_version_range`].
+fn range_contains(range: &syn::ExprRange, number: &str) -> bool {
+    let bound = |bound: &Option<Box<syn::Expr>>| match bound.as_deref() {
+        Some(syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(int),
+            ..
+        })) => int.base10_parse::<u64>().ok(),
+        _ => None,
+    };
+    let Ok(number) = number.parse::<u64>() else {
+        return false;
+    };
+    let above_start = bound(&range.start).is_none_or(|start| number >= start);
+    let below_end = bound(&range.end).is_none_or(|end| match range.limits {
+        syn::RangeLimits::HalfOpen(_) => number < end,
+        syn::RangeLimits::Closed(_) => number <= end,
+    });
+    above_start && below_end
+}
+
+/// Parses the `[...]` part of the `downgrades = [...]` option.
+///
+/// Expected format: `[StructV1, StructV2, ...]`
+///
+/// # Returns
+///
+/// A vector of version struct identifiers.
+fn parse_idents(input: syn::parse::ParseStream) -> syn::Result<Vec<syn::Ident>> {
+    let array_content;
+    syn::bracketed!(array_content in input);
+
+    let idents =
+        array_content.parse_terminated(<syn::Ident as syn::parse::Parse>::parse, syn::Token![,])?;
+    Ok(idents.into_iter().collect())
+}
+
+/// Parses the `[...]` part of the `read_only_versions = [...]` option.
+///
+/// Expected format: `["1", "2", ...]`
+///
+/// # Returns
+///
+/// A vector of version number literals.
+fn parse_version_numbers(input: syn::parse::ParseStream) -> syn::Result<Vec<syn::LitStr>> {
+    let array_content;
+    syn::bracketed!(array_content in input);
+
+    let numbers = array_content
+        .parse_terminated(<syn::LitStr as syn::parse::Parse>::parse, syn::Token![,])?;
+    Ok(numbers.into_iter().collect())
+}
+
+/// Parses the `(...)` part of the `snapshot(...)` option.
+///
+/// Expected format: `(StructV1 = "expected json", ...)`
+///
+/// # Returns
+///
+/// A vector of (`version_struct`, `expected_json`) pairs.
+fn parse_snapshots(input: syn::parse::ParseStream) -> syn::Result<Vec<(syn::Ident, syn::LitStr)>> {
+    let content;
+    syn::parenthesized!(content in input);
+
+    let mut snapshots = Vec::new();
+    while !content.is_empty() {
+        let version_struct: syn::Ident = content.parse()?;
+        content.parse::<syn::Token![=]>()?;
+        let expected: syn::LitStr = content.parse()?;
+        snapshots.push((version_struct, expected));
+
+        if content.is_empty() {
+            break;
+        }
+        content.parse::<syn::Token![,]>()?;
+    }
+    Ok(snapshots)
+}
+
+/// Parses the `[...]` part of the `deprecated_fields = [...]` option.
+///
+/// Expected format: `[StructV1::field, ...]`
+///
+/// # Returns
+///
+/// A vector of (`version_struct`, `field`) pairs.
+fn parse_deprecated_fields(
+    input: syn::parse::ParseStream,
+) -> syn::Result<Vec<(syn::Ident, syn::Ident)>> {
+    let array_content;
+    syn::bracketed!(array_content in input);
+
+    let paths =
+        syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated(&array_content)?;
+
+    paths
+        .iter()
+        .map(|path| match (path.segments.len(), path.segments.first(), path.segments.last()) {
+            (2, Some(version_struct), Some(field)) => {
+                Ok((version_struct.ident.clone(), field.ident.clone()))
+            }
+            _ => Err(syn::Error::new(
+                path.span(),
+                "Expected `VersionStruct::field`, e.g. `deprecated_fields = [UserV1::nickname]`",
+            )),
+        })
+        .collect()
+}
+
+/// A version listed in the `versions = [...]` option.
+struct VersionEntry {
+    /// Version number used as the serde tag (e.g., "1", "2", or an explicit label like "1.0")
+    number: String,
+    /// Identifier of the version struct
+    ident: syn::Ident,
+    /// Identifier of the generated enum variant, numbered by position (e.g., `Version1`)
+    variant: syn::Ident,
+    /// Attributes applied to the generated enum variant (e.g., `#[serde(alias = "v1")]`)
+    attrs: Vec<syn::Attribute>,
+}
+
+/// Parses the `[...]` part of the `versions = [...]` option.
+///
+/// Expected format: `[StructV1, StructV2, ...]`, where each entry may carry attributes
+/// for its enum variant either before or after the struct name:
+/// `[#[serde(alias = "v1")] StructV1, StructV2 #[serde(alias = "v2")]]`
+///
+/// An entry may also give its version number explicitly: `[StructV1 = "1.0", StructV2 = "2.0"]`.
+///
+/// # Returns
+///
+/// A vector of version entries, numbered starting from 1 unless given explicitly.
+fn parse_versions_list(input: syn::parse::ParseStream) -> syn::Result<Vec<VersionEntry>> {
+    // Parse the array brackets and content
+    let array_content;
+    syn::bracketed!(array_content in input);
+
+    let mut versions: Vec<VersionEntry> = Vec::new();
+    // Every malformed entry is reported, not only the first
+    let mut errors: Option<syn::Error> = None;
+    let mut report = |error: syn::Error| match &mut errors {
+        Some(errors) => errors.combine(error),
+        None => errors = Some(error),
+    };
+    let mut idx = 0;
+    while !array_content.is_empty() {
+        // Parse the struct identifier expression and any attributes around it
+        let mut attrs = array_content.call(syn::Attribute::parse_outer)?;
+        let elem: syn::Expr = array_content.parse()?;
+        attrs.extend(array_content.call(syn::Attribute::parse_outer)?);
+
+        // Convert each struct identifier to a version number (1-indexed), unless the entry
+        // gives one explicitly as `StructV1 = "1.0"`
+        idx += 1;
+        let (path, number) = match &elem {
+            syn::Expr::Assign(assign) => match &*assign.right {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(label),
+                    ..
+                }) => (&*assign.left, (label.value(), label.span())),
+                right => {
+                    report(syn::Error::new_spanned(
+                        right,
+                        "Expected a string version number, e.g. `StructV1 = \"1.0\"`",
+                    ));
+                    (&*assign.left, (idx.to_string(), right.span()))
+                }
+            },
+            elem => (elem, (idx.to_string(), elem.span())),
+        };
+        if let syn::Expr::Path(path) = path
+            && let Some(ident) = path.path.get_ident()
+        {
+            let (number, number_span) = number;
+            if versions.iter().any(|version| version.ident == *ident) {
+                report(syn::Error::new(
+                    ident.span(),
+                    format!("`{ident}` is listed more than once in `versions`"),
+                ));
+            } else if versions.iter().any(|version| version.number == number) {
+                report(syn::Error::new(
+                    number_span,
+                    format!("Version number `{number}` is used more than once in `versions`"),
+                ));
+            }
+            versions.push(VersionEntry {
+                number,
+                ident: ident.clone(),
+                variant: syn::Ident::new(&format!("Version{idx}"), ident.span()),
+                attrs,
+            });
+        } else {
+            report(syn::Error::new_spanned(
+                path,
+                "Expected a version struct name, e.g. `StructV1` or `StructV1 = \"1.0\"`",
+            ));
+        }
+
+        if array_content.is_empty() {
+            break;
+        }
+        array_content.parse::<syn::Token![,]>()?;
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(versions),
     }
 }
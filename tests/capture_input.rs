@@ -0,0 +1,60 @@
+// Input capture is a process-wide setting, so it is tested in its own binary to keep the
+// other tests capturing input
+
+use serde::{Deserialize, Serialize};
+use serde_versioned::{FormatError, Versioned};
+
+#[derive(Versioned, Serialize, Deserialize, Clone)]
+#[versioned(versions = [NoteV1], identity)]
+struct Note {
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NoteV1 {
+    pub text: String,
+}
+
+#[test]
+fn test_set_capture_input() {
+    let invalid = r#"{"version":"1","text":}"#;
+    assert!(serde_versioned::config::capture_input());
+    let Err(FormatError::Deserialize { input, .. }) =
+        Note::from_format(invalid, serde_json::from_str)
+    else {
+        panic!("Expected Deserialize error");
+    };
+    assert!(input.is_some());
+
+    serde_versioned::config::set_capture_input(false);
+    let Err(FormatError::Deserialize { input, .. }) =
+        Note::from_format(invalid, serde_json::from_str)
+    else {
+        panic!("Expected Deserialize error");
+    };
+    assert!(input.is_none());
+    let Err(FormatError::Deserialize { input, .. }) =
+        Note::from_legacy_v1(r#"{"text":}"#, serde_json::from_str)
+    else {
+        panic!("Expected Deserialize error");
+    };
+    assert!(input.is_none());
+    let Err(FormatError::Deserialize { input, .. }) =
+        serde_versioned::VersionedValue::new("1", serde_json::json!({ "text": 1 }))
+            .into_typed::<Note>()
+    else {
+        panic!("Expected Deserialize error");
+    };
+    assert!(input.is_none());
+
+    serde_versioned::config::set_capture_input(true);
+    assert!(
+        Note::from_format(invalid, serde_json::from_str)
+            .is_err_and(|error| error.to_string().contains("(input: "))
+    );
+    assert!(
+        serde_versioned::VersionedValue::new("1", serde_json::json!({ "text": 1 }))
+            .into_typed::<Note>()
+            .is_err_and(|error| error.to_string().contains("(input: "))
+    );
+}
@@ -5,18 +5,27 @@ use serde::{Deserialize, Serialize};
 use serde_versioned::Versioned;
 
 #[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
-#[versioned(versions = [UserV1, UserV2])]
+#[versioned(
+    versions = [UserV1, UserV2],
+    snapshot(
+        UserV1 = r#"{"version":"1","name":""}"#,
+        UserV2 = r#"{"version":"2","name":"","age":0}"#,
+    ),
+    clap,
+    proptest,
+    samples,
+)]
 struct User {
     pub name: String,
     pub age: u32,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default, Debug, proptest_derive::Arbitrary)]
 pub struct UserV1 {
     pub name: String,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default, Debug, proptest_derive::Arbitrary)]
 pub struct UserV2 {
     pub name: String,
     pub age: u32,
@@ -177,6 +186,50 @@ fn test_to_format_toml() {
     assert_eq!(parsed["age"].as_integer(), Some(33));
 }
 
+#[test]
+fn test_format_shorthands() {
+    let user = User {
+        name: "Kai".to_string(),
+        age: 27,
+    };
+
+    assert_eq!(User::from_json(&user.to_json().unwrap()).unwrap(), user);
+    assert_eq!(User::from_yaml(&user.to_yaml().unwrap()).unwrap(), user);
+    assert_eq!(User::from_toml(&user.to_toml().unwrap()).unwrap(), user);
+    assert_eq!(User::from_binary(&user.to_binary().unwrap()).unwrap(), user);
+
+    // Older versions are read as with from_format
+    let user = User::from_json(r#"{"version":"1","name":"Kai"}"#).unwrap();
+    assert_eq!(user.age, 0);
+    assert!(User::from_toml("version = \"9\"\n").is_err());
+}
+
+#[test]
+fn test_from_auto() {
+    let user = User {
+        name: "Kai".to_string(),
+        age: 27,
+    };
+    assert_eq!(User::from_auto(&user.to_json().unwrap()).unwrap(), user);
+    assert_eq!(User::from_auto(&user.to_toml().unwrap()).unwrap(), user);
+    assert_eq!(User::from_auto(&user.to_yaml().unwrap()).unwrap(), user);
+
+    // Conversion errors are not retried in other formats
+    let error =
+        Task::from_auto(r#"{"version":"1","title":"Ship","priority":"urgent"}"#).unwrap_err();
+    assert!(error.is_version_conversion());
+
+    let serde_versioned::FormatError::Deserialize { error, .. } =
+        User::from_auto("{version: [").unwrap_err()
+    else {
+        panic!("Expected deserialize error");
+    };
+    let message = error.to_string();
+    assert!(message.starts_with("json: "));
+    assert!(message.contains("; toml: "));
+    assert!(message.contains("; yaml: "));
+}
+
 // YAML format tests
 #[test]
 fn test_serialization_yaml() {
@@ -262,6 +315,102 @@ fn test_deserialize_error() {
     assert!(error_msg.contains("Deserialization error"));
 }
 
+#[test]
+fn test_unknown_version_error() {
+    let error = User::from_json(r#"{"version":"9","name":"Alice"}"#).unwrap_err();
+    assert!(error.is_deserialize());
+    assert!(
+        error
+            .to_string()
+            .contains("unknown version `9`, expected one of `1`, `2`")
+    );
+    let error = User::from_yaml("version: '9'\nname: Alice\n").unwrap_err();
+    assert!(error.to_string().contains("unknown version `9`"));
+
+    // A known version keeps the error of its own fields
+    let error = User::from_json(r#"{"version":"2","name":"Alice"}"#).unwrap_err();
+    assert!(error.to_string().contains("missing field `age`"));
+
+    // Extra metadata next to the tag does not get in the way of reading the version
+    let error = User::from_json(r#"{"exported_by":{"tool":"backup"},"version":"3"}"#).unwrap_err();
+    assert!(error.to_string().contains("unknown version `3`"));
+    let user = User::from_json(r#"{"exported_by":"backup","version":"2","name":"Alice","age":30}"#)
+        .unwrap();
+    assert_eq!(user.age, 30);
+
+    // The enum itself reports it, so it does not depend on the entry point or the format
+    let error =
+        User::from_format(r#"{"version":"9","name":"Alice"}"#, serde_json::from_str).unwrap_err();
+    assert!(error.to_string().contains("unknown version `9`"));
+    let error = serde_json::from_str::<UserVersion>(r#"{"version":"\u0039"}"#).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("unknown version `9`, expected one of `1`, `2`")
+    );
+    let error = Label::from_json(r#"{"version":"7","data":{"text":"a"}}"#).unwrap_err();
+    assert!(error.to_string().contains("unknown version `7`"));
+
+    #[derive(Serialize)]
+    struct Unknown {
+        version: &'static str,
+        name: &'static str,
+    }
+    let bytes = rmp_serde::to_vec(&Unknown {
+        version: "9",
+        name: "Alice",
+    })
+    .unwrap();
+    let error = User::from_binary(&bytes).unwrap_err();
+    assert!(error.to_string().contains("unknown version `9`"));
+}
+
+#[test]
+fn test_version_conversion_error_with_input() {
+    use serde_versioned::FormatInput;
+
+    let Err(error) = Task::from_format(
+        r#"{"version":"1","title":"Fix","priority":"urgent"}"#,
+        serde_json::from_str,
+    ) else {
+        panic!("Expected conversion error");
+    };
+    assert!(error.is_version_conversion());
+    assert_eq!(
+        error.input().and_then(FormatInput::as_text),
+        Some(r#"{"version":"1","title":"Fix","priority":"urgent"}"#)
+    );
+    assert!(error.to_string().contains(r#"(input: "{\"version\":\"1\""#));
+
+    // Long input is truncated
+    let long = format!(
+        r#"{{"version":"1","title":"{}","priority":"urgent"}}"#,
+        "x".repeat(4096)
+    );
+    let Err(error) = Task::from_format(&long, serde_json::from_str) else {
+        panic!("Expected conversion error");
+    };
+    let serde_versioned::FormatError::VersionConversion(error) = error else {
+        panic!("Expected conversion error");
+    };
+    assert_eq!(
+        error.input().and_then(FormatInput::as_text).unwrap().len(),
+        1024
+    );
+
+    // Displayed input is truncated at a character boundary
+    let multibyte = format!(
+        r#"{{"version":"1","title":"{}日本","priority":"urgent"}}"#,
+        "x".repeat(75)
+    );
+    assert!(!multibyte.is_char_boundary(100));
+    let Err(error) = Task::from_format(&multibyte, serde_json::from_str) else {
+        panic!("Expected conversion error");
+    };
+    let message = error.to_string();
+    assert!(message.contains(r#"xxx"...)"#), "{message}");
+}
+
 #[test]
 fn test_deserialize_error_with_input() {
     let invalid_json = r#"{"version":"1","name":}"#;
@@ -272,8 +421,8 @@ fn test_deserialize_error_with_input() {
     let error = result.unwrap_err();
     if let serde_versioned::FormatError::Deserialize { input, .. } = error {
         assert!(input.is_some());
-        let input_str = input.unwrap();
-        assert!(input_str.contains("version"));
+        let input = input.unwrap();
+        assert!(input.as_text().unwrap().contains("version"));
     } else {
         panic!("Expected Deserialize error");
     }
@@ -348,3 +497,3422 @@ fn test_version_conversion_error_version_accessor() {
     let vc_error = VersionConversionError::new("3", source_error);
     assert_eq!(vc_error.version(), "3");
 }
+
+// Async conversion tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [AccountV1, AccountV2], async)]
+struct Account {
+    pub id: u64,
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccountV1 {
+    pub id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccountV2 {
+    pub id: u64,
+    pub email: String,
+}
+
+impl serde_versioned::FromVersion<Account> for AccountV1 {
+    fn convert(self) -> Account {
+        Account {
+            id: self.id,
+            email: String::new(),
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Account> for AccountV2 {
+    fn convert(self) -> Account {
+        Account {
+            id: self.id,
+            email: self.email,
+        }
+    }
+}
+
+async fn lookup_email(id: u64) -> String {
+    format!("user{id}@example.com")
+}
+
+impl serde_versioned::AsyncFromVersion<Account> for AccountV1 {
+    async fn convert_async(self) -> Result<Account, serde_versioned::VersionConversionError> {
+        if self.id == 0 {
+            return Err(serde_versioned::VersionConversionError::new(
+                "1",
+                "no account with id 0".into(),
+            ));
+        }
+        let email = lookup_email(self.id).await;
+        Ok(Account { id: self.id, email })
+    }
+}
+
+impl serde_versioned::AsyncFromVersion<Account> for AccountV2 {
+    async fn convert_async(self) -> Result<Account, serde_versioned::VersionConversionError> {
+        Ok(serde_versioned::FromVersion::convert(self))
+    }
+}
+
+/// Polls a future to completion without an async runtime.
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    loop {
+        if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn test_from_version_async() {
+    let v1_json = r#"{"version":"1","id":7}"#;
+    let version: AccountVersion = serde_json::from_str(v1_json).unwrap();
+
+    let account = block_on(Account::from_version_async(version)).unwrap();
+    assert_eq!(account.id, 7);
+    assert_eq!(account.email, "user7@example.com");
+
+    let current = Account {
+        id: 8,
+        email: "eight@example.com".to_string(),
+    };
+    let restored = block_on(Account::from_version_async(current.to_version())).unwrap();
+    assert_eq!(current, restored);
+
+    let version: AccountVersion = serde_json::from_str(r#"{"version":"1","id":0}"#).unwrap();
+    let error = block_on(Account::from_version_async(version)).unwrap_err();
+    assert_eq!(error.context.as_deref(), Some("AccountV1 → Account"));
+
+    // The future can be spawned on a multi-threaded runtime
+    fn assert_send<T: Send>(_: &T) {}
+    let version: AccountVersion = serde_json::from_str(v1_json).unwrap();
+    assert_send(&Account::from_version_async(version));
+}
+
+// Existing From conversion tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [ShipmentV1, ShipmentV2], use_from, identity)]
+struct Shipment {
+    pub carrier: String,
+    pub weight_grams: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ShipmentV1 {
+    pub carrier: String,
+    pub weight_kg: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ShipmentV2 {
+    pub carrier: String,
+    pub weight_grams: u64,
+}
+
+impl From<ShipmentV1> for Shipment {
+    fn from(version: ShipmentV1) -> Self {
+        Shipment {
+            carrier: version.carrier,
+            weight_grams: u64::from(version.weight_kg) * 1000,
+        }
+    }
+}
+
+#[test]
+fn test_use_from() {
+    let shipment =
+        Shipment::from_json(r#"{"version":"1","carrier":"post","weight_kg":2}"#).unwrap();
+    assert_eq!(shipment.weight_grams, 2000);
+
+    let json = shipment.to_json().unwrap();
+    assert_eq!(Shipment::from_json(&json).unwrap(), shipment);
+    assert_eq!(
+        Shipment::from_latest(ShipmentV2 {
+            carrier: "rail".to_string(),
+            weight_grams: 5,
+        })
+        .carrier,
+        "rail"
+    );
+}
+
+// Context conversion tests
+/// Values supplied by the caller of a migration.
+struct MigrationContext {
+    default_locale: String,
+}
+
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [GreetingV1, GreetingV2], context = "MigrationContext")]
+struct Greeting {
+    pub name: String,
+    pub locale: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GreetingV1 {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GreetingV2 {
+    pub name: String,
+    pub locale: String,
+}
+
+impl serde_versioned::FromVersion<Greeting> for GreetingV1 {
+    fn convert(self) -> Greeting {
+        Greeting {
+            name: self.name,
+            locale: "en".to_string(),
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Greeting> for GreetingV2 {
+    fn convert(self) -> Greeting {
+        Greeting {
+            name: self.name,
+            locale: self.locale,
+        }
+    }
+}
+
+impl serde_versioned::FromVersionWith<Greeting, MigrationContext> for GreetingV1 {
+    fn convert_with(self, ctx: &MigrationContext) -> Greeting {
+        Greeting {
+            name: self.name,
+            locale: ctx.default_locale.clone(),
+        }
+    }
+}
+
+impl serde_versioned::FromVersionWith<Greeting, MigrationContext> for GreetingV2 {
+    fn convert_with(self, _ctx: &MigrationContext) -> Greeting {
+        serde_versioned::FromVersion::convert(self)
+    }
+}
+
+#[test]
+fn test_from_version_with() {
+    let ctx = MigrationContext {
+        default_locale: "ja".to_string(),
+    };
+    let version: GreetingVersion = serde_json::from_str(r#"{"version":"1","name":"Aki"}"#).unwrap();
+    let greeting = Greeting::from_version_with(version, &ctx);
+    assert_eq!(greeting.locale, "ja");
+
+    let version: GreetingVersion =
+        serde_json::from_str(r#"{"version":"2","name":"Aki","locale":"fr"}"#).unwrap();
+    assert_eq!(Greeting::from_version_with(version, &ctx).locale, "fr");
+}
+
+// Flatten tests
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    pub id: u64,
+    pub source: String,
+    #[serde(flatten)]
+    pub body: UserVersion,
+}
+
+#[test]
+fn test_flatten_version_enum_into_envelope() {
+    let json = r#"{"id":42,"source":"import","version":"1","name":"Nora"}"#;
+
+    let envelope: Envelope = serde_json::from_str(json).unwrap();
+    assert_eq!(envelope.id, 42);
+    assert_eq!(envelope.source, "import");
+
+    let user = User::from_version(envelope.body).unwrap();
+    assert_eq!(user.name, "Nora");
+    assert_eq!(user.age, 0);
+
+    // Serializing places the version tag and fields alongside the envelope fields
+    let envelope = Envelope {
+        id: 43,
+        source: "api".to_string(),
+        body: User {
+            name: "Owen".to_string(),
+            age: 31,
+        }
+        .to_version(),
+    };
+    let parsed: serde_json::Value =
+        serde_json::from_str(&serde_json::to_string(&envelope).unwrap()).unwrap();
+    assert_eq!(parsed["id"], 43);
+    assert_eq!(parsed["version"], "2");
+    assert_eq!(parsed["name"], "Owen");
+    assert_eq!(parsed["age"], 31);
+
+    // YAML envelopes work the same way
+    let yaml = "id: 44\nsource: yaml\nversion: \"2\"\nname: Pia\nage: 27\n";
+    let envelope: Envelope = serde_yaml::from_str(yaml).unwrap();
+    let user = User::from_version(envelope.body).unwrap();
+    assert_eq!(user.age, 27);
+}
+
+// Archive tests
+#[test]
+fn test_archive_and_convert() {
+    let v1_json = r#"{"version":"1","name":"Quinn"}"#;
+
+    let (archived, user) = User::archive_and_convert(v1_json, serde_json::from_str).unwrap();
+    assert_eq!(archived["version"], "1");
+    assert_eq!(archived["name"], "Quinn");
+    assert!(archived.get("age").is_none());
+    assert_eq!(user.name, "Quinn");
+    assert_eq!(user.age, 0);
+}
+
+#[test]
+fn test_archive_and_convert_deserialize_error() {
+    let result = User::archive_and_convert(r#"{"version":"1""#, serde_json::from_str);
+    assert!(result.unwrap_err().is_deserialize());
+}
+
+// Compile-fail tests
+#[test]
+fn test_compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
+
+// Stream tests
+#[test]
+fn test_migrate_stream() {
+    use tokio_stream::StreamExt;
+
+    let messages = tokio_stream::iter(vec![
+        r#"{"version":"1","name":"Rosa"}"#.to_string(),
+        r#"{"version":"2","name":"Sam","age":52}"#.to_string(),
+        r#"{"version":"2","name":}"#.to_string(),
+    ]);
+
+    let results: Vec<_> = block_on(User::migrate_stream(messages).collect());
+    assert_eq!(results.len(), 3);
+
+    let rosa = results[0].as_ref().unwrap();
+    assert_eq!(rosa.name, "Rosa");
+    assert_eq!(rosa.age, 0);
+
+    let sam = results[1].as_ref().unwrap();
+    assert_eq!(sam.name, "Sam");
+    assert_eq!(sam.age, 52);
+
+    assert!(results[2].as_ref().unwrap_err().is_deserialize());
+}
+
+// Map conversion tests
+
+/// A hand-written `Versioned` implementation whose conversion can fail.
+#[derive(Debug, PartialEq)]
+struct Temperature {
+    pub kelvin: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "version")]
+enum TemperatureVersion {
+    #[serde(rename = "1")]
+    Version1 { celsius: f64 },
+}
+
+impl Versioned for Temperature {
+    type VersionEnum = TemperatureVersion;
+
+    fn from_version(
+        version: Self::VersionEnum,
+    ) -> Result<Self, serde_versioned::VersionConversionError> {
+        match version {
+            TemperatureVersion::Version1 { celsius } => {
+                let kelvin = celsius + 273.15;
+                if kelvin < 0.0 {
+                    return Err(serde_versioned::VersionConversionError::new(
+                        "1",
+                        "temperature below absolute zero".into(),
+                    ));
+                }
+                Ok(Self { kelvin })
+            }
+        }
+    }
+
+    fn to_version(&self) -> Self::VersionEnum {
+        TemperatureVersion::Version1 {
+            celsius: self.kelvin - 273.15,
+        }
+    }
+}
+
+#[test]
+fn test_convert_map_values() {
+    use std::collections::HashMap;
+
+    let mut readings = HashMap::new();
+    readings.insert("lab", TemperatureVersion::Version1 { celsius: 20.0 });
+    readings.insert("freezer", TemperatureVersion::Version1 { celsius: -18.0 });
+    readings.insert("broken", TemperatureVersion::Version1 { celsius: -300.0 });
+
+    let converted = Temperature::convert_map_values(readings);
+    assert_eq!(converted.len(), 3);
+    assert!((converted["lab"].as_ref().unwrap().kelvin - 293.15).abs() < 1e-9);
+    assert!((converted["freezer"].as_ref().unwrap().kelvin - 255.15).abs() < 1e-9);
+
+    let error = converted["broken"].as_ref().unwrap_err();
+    assert_eq!(error.version(), "1");
+    assert!(error.to_string().contains("absolute zero"));
+}
+
+// Enum visibility tests
+mod wire {
+    use serde::{Deserialize, Serialize};
+    use serde_versioned::Versioned;
+
+    #[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+    #[versioned(versions = [MessageV1], visibility = "pub(crate)")]
+    pub struct Message {
+        pub body: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub struct MessageV1 {
+        pub body: String,
+    }
+
+    impl serde_versioned::FromVersion<Message> for MessageV1 {
+        fn convert(self) -> Message {
+            Message { body: self.body }
+        }
+    }
+}
+
+#[test]
+fn test_enum_visibility_override() {
+    let message = wire::Message {
+        body: "hello".to_string(),
+    };
+
+    // The crate-private enum is still usable within the crate
+    let version: wire::MessageVersion = message.to_version();
+    let restored = wire::Message::from_version(version).unwrap();
+    assert_eq!(message, restored);
+}
+
+// Legacy untagged tests
+#[test]
+fn test_from_legacy_v1() {
+    let legacy_json = r#"{"name":"Tess"}"#;
+
+    let user = User::from_legacy_v1(legacy_json, serde_json::from_str).unwrap();
+    assert_eq!(user.name, "Tess");
+    assert_eq!(user.age, 0);
+
+    let result = User::from_legacy_v1(r#"{"nickname":"Tess"}"#, serde_json::from_str);
+    assert!(result.unwrap_err().is_deserialize());
+}
+
+// Unknown field preservation tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [ProfileV1, ProfileV2])]
+struct Profile {
+    pub name: String,
+    pub age: u32,
+    pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProfileV1 {
+    pub name: String,
+    #[serde(flatten)]
+    pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProfileV2 {
+    pub name: String,
+    pub age: u32,
+    #[serde(flatten)]
+    pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl serde_versioned::FromVersion<Profile> for ProfileV1 {
+    fn convert(self) -> Profile {
+        Profile {
+            name: self.name,
+            age: 0,
+            extra: self.extra,
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Profile> for ProfileV2 {
+    fn convert(self) -> Profile {
+        Profile {
+            name: self.name,
+            age: self.age,
+            extra: self.extra,
+        }
+    }
+}
+
+#[test]
+fn test_unknown_fields_survive_migration() {
+    let v1_json = r#"{"version":"1","name":"Uma","nickname":"U","tags":["a","b"]}"#;
+
+    let profile = Profile::from_format(v1_json, serde_json::from_str).unwrap();
+    assert_eq!(profile.extra.len(), 2);
+    assert!(!profile.extra.contains_key("version"));
+
+    let migrated = profile.to_format(serde_json::to_string).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&migrated).unwrap();
+    assert_eq!(parsed["version"], "2");
+    assert_eq!(parsed["name"], "Uma");
+    assert_eq!(parsed["age"], 0);
+    assert_eq!(parsed["nickname"], "U");
+    assert_eq!(parsed["tags"], serde_json::json!(["a", "b"]));
+}
+
+// Version enum trait tests
+#[test]
+fn test_clone_version_enum() {
+    let version: UserVersion = serde_json::from_str(r#"{"version":"1","name":"Vera"}"#).unwrap();
+    let copy = version.clone();
+
+    let first = User::from_version(version).unwrap();
+    let second = User::from_version(copy).unwrap();
+    assert_eq!(first, second);
+    assert_eq!(second.name, "Vera");
+}
+
+#[test]
+fn test_version_for_struct() {
+    use serde_versioned::VersionMember;
+
+    assert_eq!(User::version_for::<UserV1>(), Some("1"));
+    assert_eq!(User::version_for::<UserV2>(), Some("2"));
+    assert_eq!(User::version_for::<AccountV1>(), None);
+    assert_eq!(<UserV2 as VersionMember<User>>::VERSION, "2");
+}
+
+#[test]
+fn test_latest_version_const() {
+    assert_eq!(User::LATEST_VERSION, "2");
+    assert_eq!(Release::LATEST_VERSION, "2.0");
+
+    let incoming =
+        User::peek_version(r#"{"version":"1","name":"Yun"}"#, serde_json::from_str).unwrap();
+    assert_ne!(incoming, User::LATEST_VERSION);
+    let user = User {
+        name: "Yun".to_string(),
+        age: 7,
+    };
+    assert_eq!(User::version_tag(&user.to_version()), User::LATEST_VERSION);
+}
+
+#[test]
+fn test_all_samples() {
+    let samples = User::all_samples();
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[0].0, "1");
+    assert_eq!(samples[1].0, "2");
+    assert_eq!(
+        samples[1].1,
+        User {
+            name: String::new(),
+            age: 0,
+        }
+    );
+}
+
+#[test]
+fn test_discriminant() {
+    let v1: UserVersion = UserV1 {
+        name: "Abe".to_string(),
+    }
+    .into();
+    let v2: UserVersion = UserV2 {
+        name: "Abe".to_string(),
+        age: 3,
+    }
+    .into();
+    assert_eq!(User::discriminant(&v1), 1);
+    assert_eq!(User::discriminant(&v2), 2);
+
+    // Versions that are not integers are numbered by position
+    let release = Release {
+        name: "Launch".to_string(),
+        draft: false,
+    };
+    assert_eq!(Release::discriminant(&release.to_version()), 2);
+}
+
+#[test]
+fn test_as_version() {
+    let version = UserVersion::Version1(UserV1 {
+        name: "Cy".to_string(),
+    });
+    let v1: &UserV1 = User::as_version(&version).unwrap();
+    assert_eq!(v1.name, "Cy");
+    assert!(User::as_version::<UserV2>(&version).is_none());
+    assert!(User::as_version::<String>(&version).is_none());
+}
+
+#[test]
+fn test_variant_accessors() {
+    let version: UserVersion =
+        serde_json::from_str(r#"{"version":"2","name":"Cy","age":5}"#).unwrap();
+    assert_eq!(version.as_version_2().unwrap().age, 5);
+    assert!(version.as_version_1().is_none());
+
+    // A single version has an accessor as well
+    let version = AuditEntry {
+        action: "login".to_string(),
+    }
+    .to_version();
+    assert_eq!(version.as_version_1().unwrap().action, "login");
+}
+
+#[test]
+fn test_version_struct_into_enum() {
+    let v1: UserVersion = UserV1 {
+        name: "Wes".to_string(),
+    }
+    .into();
+    assert_eq!(
+        serde_json::to_string(&v1).unwrap(),
+        r#"{"version":"1","name":"Wes"}"#
+    );
+
+    let v2: UserVersion = UserV2 {
+        name: "Xia".to_string(),
+        age: 19,
+    }
+    .into();
+    assert_eq!(User::version_tag(&v2), "2");
+    assert_eq!(User::from_version(v2).unwrap().age, 19);
+}
+
+// Identity conversion tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [SettingV1], identity)]
+struct Setting {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SettingV1 {
+    pub key: String,
+    pub value: String,
+}
+
+#[test]
+fn test_identity_conversion_single_version() {
+    let setting = Setting::from_format(
+        r#"{"version":"1","key":"theme","value":"dark"}"#,
+        serde_json::from_str,
+    )
+    .unwrap();
+    assert_eq!(setting.key, "theme");
+    assert_eq!(setting.value, "dark");
+
+    let restored = Setting::from_version(setting.to_version()).unwrap();
+    assert_eq!(setting, restored);
+}
+
+#[test]
+fn test_format_error_map_deserialize() {
+    use serde_versioned::{FormatError, FormatInput};
+
+    #[derive(Debug)]
+    enum AppError {
+        Json(serde_json::Error),
+        Yaml(serde_yaml::Error),
+    }
+
+    impl std::fmt::Display for AppError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::Json(e) => write!(f, "json: {e}"),
+                Self::Yaml(e) => write!(f, "yaml: {e}"),
+            }
+        }
+    }
+
+    impl std::error::Error for AppError {}
+
+    let json_error: FormatError<AppError> =
+        User::from_format(r#"{"version":"1""#, serde_json::from_str)
+            .unwrap_err()
+            .map_deserialize(AppError::Json);
+    let yaml_error: FormatError<AppError> = User::from_format("version: [", serde_yaml::from_str)
+        .unwrap_err()
+        .map_deserialize(AppError::Yaml);
+
+    let errors = [json_error, yaml_error];
+    match &errors[0] {
+        FormatError::Deserialize { error, input } => {
+            assert!(matches!(error, AppError::Json(_)));
+            assert_eq!(
+                input.as_ref().and_then(FormatInput::as_text),
+                Some(r#"{"version":"1""#)
+            );
+        }
+        _ => panic!("Expected Deserialize error"),
+    }
+    assert!(errors[1].to_string().contains("yaml: "));
+}
+
+#[test]
+fn test_format_error_erase() {
+    use serde_versioned::{ErasedError, FormatError};
+    use std::error::Error;
+
+    let errors: Vec<FormatError<ErasedError>> = vec![
+        User::from_json(r#"{"version":"1""#).unwrap_err().erase(),
+        User::from_yaml("version: [").unwrap_err().erase(),
+        Task::from_json(r#"{"version":"1","title":"Ship","priority":"urgent"}"#)
+            .unwrap_err()
+            .erase(),
+        FormatError::serialize(std::io::Error::other("disk full")).erase(),
+    ];
+
+    // The variant, the message, and the source survive erasing the error type
+    assert!(errors[0].is_deserialize());
+    match &errors[1] {
+        FormatError::Deserialize { error, input } => {
+            assert!(error.downcast_ref::<serde_yaml::Error>().is_some());
+            assert!(input.is_some());
+        }
+        _ => panic!("Expected Deserialize error"),
+    }
+    assert!(errors[2].is_version_conversion());
+    assert!(errors[3].is_serialize());
+    assert_eq!(errors[3].to_string(), "Serialization error: disk full");
+    assert_eq!(errors[3].source().unwrap().to_string(), "disk full");
+    assert!(errors.iter().all(|error| error.source().is_some()));
+}
+
+// Deprecated field tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [ContactV1, ContactV2], deprecated_fields = [ContactV1::fax])]
+struct Contact {
+    pub phone: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ContactV1 {
+    pub phone: String,
+    #[deprecated]
+    #[serde(default)]
+    pub fax: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ContactV2 {
+    pub phone: String,
+}
+
+impl serde_versioned::FromVersion<Contact> for ContactV1 {
+    fn convert(self) -> Contact {
+        Contact { phone: self.phone }
+    }
+}
+
+impl serde_versioned::FromVersion<Contact> for ContactV2 {
+    fn convert(self) -> Contact {
+        Contact { phone: self.phone }
+    }
+}
+
+/// The name and debug-formatted value of each field of an event.
+type EventFields = Vec<(String, String)>;
+
+/// A `tracing` subscriber recording the fields of each event so tests can assert on them.
+#[derive(Default, Clone)]
+struct RecordingSubscriber {
+    events: std::sync::Arc<std::sync::Mutex<Vec<EventFields>>>,
+}
+
+impl tracing::Subscriber for RecordingSubscriber {
+    fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        tracing::span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        struct Fields(EventFields);
+
+        impl tracing::field::Visit for Fields {
+            fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+                self.0
+                    .push((field.name().to_string(), format!("{value:?}")));
+            }
+        }
+
+        let mut fields = Fields(Vec::new());
+        event.record(&mut fields);
+        self.events.lock().unwrap().push(fields.0);
+    }
+
+    fn enter(&self, _span: &tracing::span::Id) {}
+
+    fn exit(&self, _span: &tracing::span::Id) {}
+}
+
+#[test]
+fn test_tracing_conversion_events() {
+    let subscriber = RecordingSubscriber::default();
+    let events = subscriber.events.clone();
+    tracing::subscriber::with_default(subscriber, || {
+        User::from_format(r#"{"version":"1","name":"Tove"}"#, serde_json::from_str).unwrap();
+        Task::from_format(
+            r#"{"version":"1","title":"Ship","priority":"urgent"}"#,
+            serde_json::from_str,
+        )
+        .unwrap_err();
+    });
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    let field = |event: &[(String, String)], name: &str| {
+        event
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.clone())
+    };
+    assert_eq!(field(&events[0], "version").as_deref(), Some("\"1\""));
+    assert_eq!(field(&events[0], "to").as_deref(), Some("\"User\""));
+    assert_eq!(field(&events[0], "success").as_deref(), Some("true"));
+    assert_eq!(field(&events[1], "to").as_deref(), Some("\"Task\""));
+    assert_eq!(field(&events[1], "success").as_deref(), Some("false"));
+    assert!(
+        field(&events[1], "error")
+            .unwrap()
+            .contains("unknown priority")
+    );
+}
+
+// Deprecated version tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [MemoV1, MemoV2], deprecated = [MemoV1], async)]
+struct Memo {
+    pub text: String,
+}
+
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [MemoV1, MemoV2], deprecated = [MemoV1], deny_deprecated, async)]
+struct StrictMemo {
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MemoV1 {
+    pub body: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MemoV2 {
+    pub text: String,
+}
+
+impl serde_versioned::FromVersion<Memo> for MemoV1 {
+    fn convert(self) -> Memo {
+        Memo { text: self.body }
+    }
+}
+
+impl serde_versioned::FromVersion<Memo> for MemoV2 {
+    fn convert(self) -> Memo {
+        Memo { text: self.text }
+    }
+}
+
+impl serde_versioned::FromVersion<StrictMemo> for MemoV2 {
+    fn convert(self) -> StrictMemo {
+        StrictMemo { text: self.text }
+    }
+}
+
+impl serde_versioned::AsyncFromVersion<Memo> for MemoV1 {
+    async fn convert_async(self) -> Result<Memo, serde_versioned::VersionConversionError> {
+        Ok(serde_versioned::FromVersion::convert(self))
+    }
+}
+
+impl serde_versioned::AsyncFromVersion<Memo> for MemoV2 {
+    async fn convert_async(self) -> Result<Memo, serde_versioned::VersionConversionError> {
+        Ok(serde_versioned::FromVersion::convert(self))
+    }
+}
+
+impl serde_versioned::AsyncFromVersion<StrictMemo> for MemoV2 {
+    async fn convert_async(self) -> Result<StrictMemo, serde_versioned::VersionConversionError> {
+        Ok(serde_versioned::FromVersion::convert(self))
+    }
+}
+
+#[test]
+fn test_deprecated_versions() {
+    let subscriber = RecordingSubscriber::default();
+    let events = subscriber.events.clone();
+    tracing::subscriber::with_default(subscriber, || {
+        Memo::from_json(r#"{"version":"1","body":"hi"}"#).unwrap();
+        Memo::from_json(r#"{"version":"1","body":"hi"}"#).unwrap();
+        Memo::from_json(r#"{"version":"2","text":"hi"}"#).unwrap();
+    });
+
+    // Each read of the deprecated version warns, next to the conversion events
+    let events = events.lock().unwrap();
+    let warnings: Vec<_> = events
+        .iter()
+        .filter(|event| event.iter().any(|(field, _)| field == "version_struct"))
+        .collect();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].contains(&("version_struct".to_string(), "\"MemoV1\"".to_string())));
+
+    // Denied versions are rejected without converting them
+    let error = StrictMemo::from_json(r#"{"version":"1","body":"hi"}"#).unwrap_err();
+    assert!(error.is_version_conversion());
+    assert!(error.to_string().contains("deprecated"));
+    assert_eq!(
+        StrictMemo::from_json(r#"{"version":"2","text":"hi"}"#).unwrap(),
+        StrictMemo {
+            text: "hi".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_deprecated_versions_async() {
+    let subscriber = RecordingSubscriber::default();
+    let events = subscriber.events.clone();
+    tracing::subscriber::with_default(subscriber, || {
+        let version: MemoVersion = serde_json::from_str(r#"{"version":"1","body":"hi"}"#).unwrap();
+        block_on(Memo::from_version_async(version)).unwrap();
+    });
+    let events = events.lock().unwrap();
+    assert!(events.iter().any(|event| {
+        event.contains(&("version_struct".to_string(), "\"MemoV1\"".to_string()))
+    }));
+
+    let version: StrictMemoVersion =
+        serde_json::from_str(r#"{"version":"1","body":"hi"}"#).unwrap();
+    let error = block_on(StrictMemo::from_version_async(version)).unwrap_err();
+    assert!(error.to_string().contains("deprecated"));
+}
+
+/// A logger recording warnings so tests can assert on them.
+struct RecordingLogger;
+
+static LOGGED_WARNINGS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+impl log::Log for RecordingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            LOGGED_WARNINGS
+                .lock()
+                .unwrap()
+                .push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn test_deprecated_field_warning() {
+    static LOGGER: RecordingLogger = RecordingLogger;
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+
+    let deprecated_warnings = || {
+        LOGGED_WARNINGS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|message| message.contains("`fax`"))
+            .count()
+    };
+
+    // An empty deprecated field does not warn
+    Contact::from_format(r#"{"version":"1","phone":"123"}"#, serde_json::from_str).unwrap();
+    assert_eq!(deprecated_warnings(), 0);
+
+    // A populated deprecated field warns once
+    let json = r#"{"version":"1","phone":"123","fax":"456"}"#;
+    Contact::from_format(json, serde_json::from_str).unwrap();
+    Contact::from_format(json, serde_json::from_str).unwrap();
+    assert_eq!(deprecated_warnings(), 1);
+
+    let warnings = LOGGED_WARNINGS.lock().unwrap();
+    assert!(warnings.iter().any(|message| message.contains("ContactV1")));
+}
+
+#[test]
+fn test_to_format_tagged() {
+    let user = User {
+        name: "Wade".to_string(),
+        age: 61,
+    };
+
+    let (json, version) = user.to_format_tagged(serde_json::to_string).unwrap();
+    assert_eq!(version, "2");
+
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed["version"], version);
+    assert_eq!(parsed["name"], "Wade");
+}
+
+#[test]
+fn test_version_tag() {
+    let v1: UserVersion = serde_json::from_str(r#"{"version":"1","name":"Xia"}"#).unwrap();
+    assert_eq!(User::version_tag(&v1), "1");
+    assert_eq!(User::version_tag(&v1), User::extract_version_string(&v1));
+}
+
+#[test]
+fn test_serialize_error() {
+    use std::error::Error;
+
+    let user = User {
+        name: "Yuri".to_string(),
+        age: 44,
+    };
+
+    let error = user
+        .to_format_tagged(|_| Err::<String, _>(std::io::Error::other("disk full")))
+        .unwrap_err();
+    assert!(error.is_serialize());
+    assert!(!error.is_deserialize());
+    assert!(error.source().is_some());
+    assert!(error.to_string().contains("Serialization error: disk full"));
+}
+
+// Per-version attribute tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [TicketV1 #[serde(alias = "v1")], #[serde(alias = "v2")] TicketV2])]
+struct Ticket {
+    pub title: String,
+    pub priority: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TicketV1 {
+    pub title: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TicketV2 {
+    pub title: String,
+    pub priority: u8,
+}
+
+impl serde_versioned::FromVersion<Ticket> for TicketV1 {
+    fn convert(self) -> Ticket {
+        Ticket {
+            title: self.title,
+            priority: 0,
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Ticket> for TicketV2 {
+    fn convert(self) -> Ticket {
+        Ticket {
+            title: self.title,
+            priority: self.priority,
+        }
+    }
+}
+
+#[test]
+fn test_per_version_attributes() {
+    // Legacy tags are accepted through the per-version aliases
+    let ticket =
+        Ticket::from_format(r#"{"version":"v1","title":"Fix"}"#, serde_json::from_str).unwrap();
+    assert_eq!(ticket.title, "Fix");
+    let ticket = Ticket::from_format(
+        r#"{"version":"v2","title":"Ship","priority":3}"#,
+        serde_json::from_str,
+    )
+    .unwrap();
+    assert_eq!(ticket.priority, 3);
+
+    // Only version 1 denies unknown fields
+    let result = Ticket::from_format(
+        r#"{"version":"1","title":"Fix","owner":"zoe"}"#,
+        serde_json::from_str,
+    );
+    assert!(result.unwrap_err().is_deserialize());
+    let result = Ticket::from_format(
+        r#"{"version":"2","title":"Ship","priority":3,"owner":"zoe"}"#,
+        serde_json::from_str,
+    );
+    assert!(result.is_ok());
+
+    // The canonical tag is still written
+    let json = ticket.to_format(serde_json::to_string).unwrap();
+    assert!(json.contains(r#""version":"2""#));
+}
+
+#[test]
+fn test_version_count_reachable_from() {
+    assert_eq!(User::version_count_reachable_from("1"), 1);
+    assert_eq!(User::version_count_reachable_from("2"), 1);
+    assert_eq!(User::version_count_reachable_from("99"), 0);
+}
+
+// Sidecar version tests
+#[test]
+fn test_from_body_and_version() {
+    // The body and its version are stored in separate columns
+    let rows = [
+        ("1", r#"{"name":"Abe"}"#),
+        ("2", r#"{"name":"Bea","age":38}"#),
+    ];
+
+    let users: Vec<User> = rows
+        .iter()
+        .map(|(version, body)| {
+            User::from_body_and_version(version, &mut serde_json::Deserializer::from_str(body))
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(users[0].name, "Abe");
+    assert_eq!(users[0].age, 0);
+    assert_eq!(users[1].age, 38);
+
+    // Any self-describing value works as the body too
+    let body: serde_yaml::Value = serde_yaml::from_str("name: Cal\n").unwrap();
+    let user = User::from_body_and_version("1", body).unwrap();
+    assert_eq!(user.name, "Cal");
+
+    let error = User::from_body_and_version("9", &mut serde_json::Deserializer::from_str("{}"))
+        .unwrap_err();
+    assert!(error.is_deserialize());
+    assert!(error.to_string().contains("unknown version `9`"));
+}
+
+#[test]
+fn test_upgrade_path() {
+    assert_eq!(User::upgrade_path("1"), vec!["1", "2"]);
+    assert_eq!(User::upgrade_path("2"), vec!["2"]);
+    assert!(User::upgrade_path("3").is_empty());
+    assert_eq!(Setting::upgrade_path("1"), vec!["1"]);
+}
+
+#[test]
+fn test_all_versions() {
+    assert_eq!(User::all_versions(), ["1", "2"]);
+    assert!(User::all_versions().contains(&"2"));
+    assert!(!User::all_versions().contains(&"3"));
+    assert_eq!(Setting::all_versions(), ["1"]);
+}
+
+#[test]
+fn test_version_manifest() {
+    let manifest = User::version_manifest();
+    assert_eq!(manifest.tag, "version");
+    assert_eq!(
+        manifest.versions,
+        [
+            ("1", std::any::type_name::<UserV1>()),
+            ("2", std::any::type_name::<UserV2>()),
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(&manifest).unwrap()["versions"][1][0],
+        "2"
+    );
+    assert_eq!(Schedule::version_manifest().tag, "_schema");
+}
+
+// Versioned value tests
+#[test]
+fn test_versioned_value_into_typed() {
+    use serde_versioned::VersionedValue;
+
+    let stored = VersionedValue::new("1", serde_json::json!({ "name": "Dana" }));
+    let user: User = stored.into_typed().unwrap();
+    assert_eq!(user.name, "Dana");
+    assert_eq!(user.age, 0);
+
+    // Round-trip through the storage representation
+    let user = User {
+        name: "Eli".to_string(),
+        age: 19,
+    };
+    let stored = VersionedValue::from_typed(&user).unwrap();
+    assert_eq!(stored.version, "2");
+    assert!(stored.value.get("version").is_none());
+
+    let json = serde_json::to_string(&stored).unwrap();
+    let restored: VersionedValue = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.into_typed::<User>().unwrap(), user);
+}
+
+#[test]
+fn test_versioned_value_errors() {
+    use serde_versioned::VersionedValue;
+
+    let not_object = VersionedValue::new("1", serde_json::json!(["Dana"]));
+    assert!(
+        not_object
+            .into_typed::<User>()
+            .unwrap_err()
+            .is_deserialize()
+    );
+
+    let unknown_version = VersionedValue::new("7", serde_json::json!({ "name": "Dana" }));
+    assert!(
+        unknown_version
+            .into_typed::<User>()
+            .unwrap_err()
+            .is_deserialize()
+    );
+}
+
+// Move-based conversion tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [DocumentV1], identity)]
+struct Document {
+    pub title: std::borrow::Cow<'static, str>,
+    pub body: std::borrow::Cow<'static, str>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DocumentV1 {
+    pub title: std::borrow::Cow<'static, str>,
+    pub body: std::borrow::Cow<'static, str>,
+}
+
+#[test]
+fn test_into_version_moves_cow_fields() {
+    use std::borrow::Cow;
+
+    let body = "x".repeat(1024);
+    let body_ptr = body.as_ptr();
+    let document = Document {
+        title: Cow::Borrowed("Notes"),
+        body: Cow::Owned(body),
+    };
+
+    let DocumentVersion::Version1(v1) = document.clone().into_version();
+    assert!(matches!(v1.title, Cow::Borrowed("Notes")));
+    assert_eq!(v1.body.len(), 1024);
+
+    // The owned buffer is moved rather than copied
+    let DocumentVersion::Version1(v1) = document.into_version();
+    assert_eq!(v1.body.as_ptr(), body_ptr);
+
+    let restored = Document::from_version(DocumentVersion::Version1(v1)).unwrap();
+    assert_eq!(restored.title, "Notes");
+}
+
+// JSON bytes tests
+#[test]
+fn test_from_json_bytes() {
+    let user = User::from_json_bytes(br#"{"version":"1","name":"Fay"}"#).unwrap();
+    assert_eq!(user.name, "Fay");
+    assert_eq!(user.age, 0);
+
+    let user = User::from_json_bytes(br#"{"version":"2","name":"Gus","age":70}"#).unwrap();
+    assert_eq!(user.age, 70);
+}
+
+#[test]
+fn test_from_json_bytes_invalid() {
+    use serde_versioned::FormatInput;
+
+    // Invalid UTF-8 inside a string is reported as such, capturing only the length
+    let bytes = b"{\"version\":\"1\",\"name\":\"\xff\xfe\"}";
+    let error = User::from_json_bytes(bytes).unwrap_err();
+    assert!(error.is_deserialize());
+    assert!(error.to_string().contains("invalid UTF-8"));
+    assert_eq!(
+        error.input(),
+        Some(&FormatInput::Bytes { len: bytes.len() })
+    );
+
+    // Malformed JSON is captured as text
+    let error = User::from_json_bytes(b"{\"version\":").unwrap_err();
+    assert!(error.is_deserialize());
+    assert!(!error.to_string().contains("invalid UTF-8"));
+    assert_eq!(
+        error.input().and_then(FormatInput::as_text),
+        Some(r#"{"version":"#)
+    );
+
+    // So is the input of a failed conversion
+    let input = r#"{"version":"1","title":"Fix","priority":"urgent"}"#;
+    let error = Task::from_json_bytes(input.as_bytes()).unwrap_err();
+    assert!(error.is_version_conversion());
+    assert_eq!(error.input().and_then(FormatInput::as_text), Some(input));
+}
+
+// clap value parser tests
+#[test]
+fn test_value_parser() {
+    let command = clap::Command::new("export").arg(
+        clap::Arg::new("format-version")
+            .long("format-version")
+            .value_parser(User::value_parser()),
+    );
+
+    let matches = command
+        .clone()
+        .try_get_matches_from(["export", "--format-version", "1"])
+        .unwrap();
+    assert_eq!(
+        matches
+            .get_one::<String>("format-version")
+            .map(String::as_str),
+        Some("1")
+    );
+
+    let error = command
+        .try_get_matches_from(["export", "--format-version", "3"])
+        .unwrap_err();
+    assert_eq!(error.kind(), clap::error::ErrorKind::InvalidValue);
+}
+
+// Wire compatibility tests
+#[test]
+fn test_verify_wire_compatibility() {
+    User::verify_wire_compatibility(
+        r#"{"version":"1","name":"Hana"}"#,
+        r#"{"version":"2","name":"Hana","age":0}"#,
+        serde_json::from_str,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_verify_wire_compatibility_mismatch() {
+    let error = User::verify_wire_compatibility(
+        r#"{"version":"1","name":"Hana"}"#,
+        r#"{"version":"2","name":"Hana","age":5}"#,
+        serde_json::from_str,
+    )
+    .unwrap_err();
+    assert!(error.is_version_conversion());
+    if let serde_versioned::FormatError::VersionConversion(error) = error {
+        assert_eq!(error.version, "2");
+        assert_eq!(
+            error.context.as_deref(),
+            Some("old and new payloads diverge")
+        );
+        assert!(error.source.to_string().contains("age: 5"));
+    }
+}
+
+// Roundtrip helper tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [RouteV1], identity, test_roundtrip = "Route::sample")]
+struct Route {
+    pub path: String,
+    pub methods: Vec<String>,
+}
+
+impl Route {
+    fn sample() -> Self {
+        Route {
+            path: "/users".to_string(),
+            methods: vec!["GET".to_string(), "POST".to_string()],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RouteV1 {
+    pub path: String,
+    pub methods: Vec<String>,
+}
+
+#[test]
+fn test_assert_roundtrip() {
+    Route::assert_roundtrip(&Route {
+        path: String::new(),
+        methods: Vec::new(),
+    });
+}
+
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [HandlerV1], identity, test_roundtrip)]
+struct Handler {
+    pub name: String,
+    pub timeout: u32,
+}
+
+// The latest version never writes `timeout`, so it reads back as the default
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HandlerV1 {
+    pub name: String,
+    #[serde(skip)]
+    pub timeout: u32,
+}
+
+#[test]
+#[should_panic(expected = "`Handler` changed after a roundtrip")]
+fn test_assert_roundtrip_lossy() {
+    Handler::assert_roundtrip(&Handler {
+        name: "upload".to_string(),
+        timeout: 30,
+    });
+}
+
+// Restricted from_version tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [InvoiceV1, InvoiceV2], read_only_versions = ["2"], async)]
+struct Invoice {
+    pub total: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InvoiceV1 {
+    pub total: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InvoiceV2 {
+    pub total: u64,
+}
+
+impl serde_versioned::FromVersion<Invoice> for InvoiceV1 {
+    fn convert(self) -> Invoice {
+        Invoice {
+            total: u64::from(self.total),
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Invoice> for InvoiceV2 {
+    fn convert(self) -> Invoice {
+        Invoice { total: self.total }
+    }
+}
+
+impl serde_versioned::AsyncFromVersion<Invoice> for InvoiceV2 {
+    async fn convert_async(self) -> Result<Invoice, serde_versioned::VersionConversionError> {
+        Ok(serde_versioned::FromVersion::convert(self))
+    }
+}
+
+#[test]
+fn test_read_only_versions() {
+    let invoice =
+        Invoice::from_format(r#"{"version":"2","total":12}"#, serde_json::from_str).unwrap();
+    assert_eq!(invoice.total, 12);
+
+    let error =
+        Invoice::from_format(r#"{"version":"1","total":12}"#, serde_json::from_str).unwrap_err();
+    assert!(error.is_version_conversion());
+    if let serde_versioned::FormatError::VersionConversion(error) = error {
+        assert_eq!(error.version, "1");
+    }
+}
+
+#[test]
+fn test_read_only_versions_async() {
+    let version: InvoiceVersion = serde_json::from_str(r#"{"version":"2","total":12}"#).unwrap();
+    assert_eq!(
+        block_on(Invoice::from_version_async(version))
+            .unwrap()
+            .total,
+        12
+    );
+
+    let version: InvoiceVersion = serde_json::from_str(r#"{"version":"1","total":12}"#).unwrap();
+    let error = block_on(Invoice::from_version_async(version)).unwrap_err();
+    assert_eq!(error.version, "1");
+    assert!(
+        error
+            .to_string()
+            .contains("version is not listed in `read_only_versions`")
+    );
+}
+
+// Borrowed deserialization tests
+#[derive(Versioned, Serialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [CaptionV1], identity)]
+struct Caption {
+    pub text: &'static str,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CaptionV1 {
+    pub text: &'static str,
+}
+
+#[test]
+fn test_from_format_borrowed() {
+    const INPUT: &str = r#"{"version":"1","text":"fragile"}"#;
+    let caption = Caption::from_format_borrowed(INPUT, serde_json::from_str).unwrap();
+    assert_eq!(caption.text, "fragile");
+
+    // The field points into the input rather than at a copy of it
+    let input_range = INPUT.as_bytes().as_ptr_range();
+    assert!(input_range.contains(&caption.text.as_ptr()));
+
+    assert!(Caption::from_format_borrowed(r#"{"version":"1"}"#, serde_json::from_str).is_err());
+}
+
+// Latest version extraction tests
+#[test]
+fn test_into_latest() {
+    let version = User {
+        name: "Iris".to_string(),
+        age: 33,
+    }
+    .to_version();
+    let latest = User::into_latest(version).unwrap();
+    assert_eq!(latest.name, "Iris");
+    assert_eq!(latest.age, 33);
+
+    let version = UserVersion::Version1(UserV1 {
+        name: "Iris".to_string(),
+    });
+    assert!(User::into_latest(version).is_none());
+
+    // With a single version, every value is the latest
+    let version = SettingVersion::Version1(SettingV1 {
+        key: "theme".to_string(),
+        value: "dark".to_string(),
+    });
+    assert_eq!(Setting::into_latest(version).unwrap().key, "theme");
+}
+
+// proptest strategy tests
+proptest::proptest! {
+    #[test]
+    fn test_version_strategy(version in User::version_strategy()) {
+        let tag = User::version_tag(&version);
+        let user = User::from_version(version).unwrap();
+        if tag == "2" {
+            proptest::prop_assert_eq!(User::from_version(user.to_version()).unwrap(), user);
+        }
+    }
+
+    #[test]
+    fn test_arbitrary_version(version in proptest::arbitrary::any::<UserVersion>()) {
+        proptest::prop_assert!(User::from_version(version).is_ok());
+    }
+}
+
+// Tag value tests
+#[test]
+fn test_tag_value() {
+    let version = User {
+        name: "Jun".to_string(),
+        age: 20,
+    }
+    .to_version();
+    assert_eq!(User::tag_value(&version), serde_json::json!("2"));
+    assert_eq!(
+        User::tag_value(&version),
+        serde_json::to_value(&version).unwrap()["version"]
+    );
+}
+
+#[test]
+fn test_int_tag_value() {
+    let version = Gauge {
+        name: "latency".to_string(),
+        unit: "ms".to_string(),
+    }
+    .to_version();
+    assert_eq!(Gauge::tag_value(&version), serde_json::json!(2));
+    assert_eq!(
+        Gauge::tag_value(&version),
+        serde_json::to_value(&version).unwrap()["schema"]
+    );
+}
+
+// Fallible conversion tests
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum Priority {
+    Low,
+    High,
+}
+
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [TaskV1, TaskV2], downgrades = [TaskV1])]
+struct Task {
+    pub title: String,
+    pub priority: Priority,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TaskV1 {
+    pub title: String,
+    pub priority: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TaskV2 {
+    pub title: String,
+    pub priority: Priority,
+}
+
+impl serde_versioned::TryFromVersion<Task> for TaskV1 {
+    fn try_convert(self) -> Result<Task, serde_versioned::VersionConversionError> {
+        let priority = match self.priority.as_str() {
+            "low" => Priority::Low,
+            "high" => Priority::High,
+            other => {
+                return Err(serde_versioned::VersionConversionError::new(
+                    "1",
+                    format!("unknown priority `{other}`").into(),
+                ));
+            }
+        };
+        Ok(Task {
+            title: self.title,
+            priority,
+        })
+    }
+}
+
+impl serde_versioned::FromVersion<Task> for TaskV2 {
+    fn convert(self) -> Task {
+        Task {
+            title: self.title,
+            priority: self.priority,
+        }
+    }
+}
+
+#[test]
+fn test_try_from_version() {
+    let task = Task::from_format(
+        r#"{"version":"1","title":"Ship","priority":"high"}"#,
+        serde_json::from_str,
+    )
+    .unwrap();
+    assert_eq!(task.priority, Priority::High);
+
+    let task = Task::from_format(
+        r#"{"version":"2","title":"Ship","priority":"Low"}"#,
+        serde_json::from_str,
+    )
+    .unwrap();
+    assert_eq!(task.priority, Priority::Low);
+}
+
+#[test]
+fn test_try_from_version_error() {
+    let version = TaskVersion::Version1(TaskV1 {
+        title: "Ship".to_string(),
+        priority: "urgent".to_string(),
+    });
+    let error = Task::from_version(version).unwrap_err();
+    assert_eq!(error.version, "1");
+    assert_eq!(error.version_index(), Some(0));
+    assert!(error.version_index() < Some(1));
+    assert_eq!(error.source.to_string(), "unknown priority `urgent`");
+    assert_eq!(
+        error.to_string(),
+        "Failed to convert from version 1 (TaskV1 → Task): unknown priority `urgent`"
+    );
+
+    let error = Task::from_legacy_v1(
+        r#"{"title":"Ship","priority":"urgent"}"#,
+        serde_json::from_str,
+    )
+    .unwrap_err();
+    assert!(error.is_version_conversion());
+}
+
+#[test]
+fn test_from_format_all() {
+    let records = [
+        r#"{"version":"1","title":"Ship","priority":"high"}"#,
+        r#"{"version":"2","title":"#,
+        r#"{"version":"1","title":"Ship","priority":"urgent"}"#,
+        r#"{"version":"2","title":"Test","priority":"Low"}"#,
+    ];
+    let tasks = Task::from_format_all(records, serde_json::from_str);
+    assert_eq!(tasks.len(), 4);
+    assert_eq!(tasks[0].as_ref().unwrap().priority, Priority::High);
+    assert!(tasks[1].as_ref().unwrap_err().is_deserialize());
+    assert!(tasks[2].as_ref().unwrap_err().is_version_conversion());
+    assert_eq!(tasks[3].as_ref().unwrap().title, "Test");
+}
+
+#[test]
+fn test_from_format_with_version() {
+    let (task, version) = Task::from_format_with_version(
+        r#"{"version":"1","title":"Ship","priority":"high"}"#,
+        serde_json::from_str,
+    )
+    .unwrap();
+    assert_eq!(task.priority, Priority::High);
+    assert_eq!(version, "1");
+
+    let (_, version) = Task::from_format_with_version(
+        r#"{"version":"2","title":"Test","priority":"Low"}"#,
+        serde_json::from_str,
+    )
+    .unwrap();
+    assert_eq!(version, "2");
+
+    let error = Task::from_format_with_version(
+        r#"{"version":"1","title":"Ship","priority":"urgent"}"#,
+        serde_json::from_str,
+    )
+    .unwrap_err();
+    assert!(error.is_version_conversion());
+}
+
+// Fallible version extraction tests
+
+/// A hand-written `Versioned` implementation that keeps records of unknown versions.
+#[derive(Debug, PartialEq)]
+struct Signal {
+    pub strength: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SignalVersion {
+    Version1 { version: String, strength: u8 },
+    Unknown(serde_json::Value),
+}
+
+impl Versioned for Signal {
+    type VersionEnum = SignalVersion;
+
+    fn from_version(
+        version: Self::VersionEnum,
+    ) -> Result<Self, serde_versioned::VersionConversionError> {
+        match version {
+            SignalVersion::Version1 { strength, .. } => Ok(Self { strength }),
+            SignalVersion::Unknown(_) => Err(serde_versioned::VersionConversionError::new(
+                "unknown",
+                "unsupported signal version".into(),
+            )),
+        }
+    }
+
+    fn to_version(&self) -> Self::VersionEnum {
+        SignalVersion::Version1 {
+            version: "1".to_string(),
+            strength: self.strength,
+        }
+    }
+
+    fn known_version_tag(version: &Self::VersionEnum) -> Option<&'static str> {
+        match version {
+            SignalVersion::Version1 { .. } => Some("1"),
+            SignalVersion::Unknown(_) => None,
+        }
+    }
+}
+
+#[test]
+fn test_try_extract_version() {
+    let version = Signal { strength: 3 }.to_version();
+    assert_eq!(Signal::try_extract_version(&version).unwrap(), "1");
+
+    let version = SignalVersion::Unknown(serde_json::json!({"version": "7", "bars": 4}));
+    assert_eq!(Signal::try_extract_version(&version).unwrap(), "7");
+    let version = SignalVersion::Unknown(serde_json::json!({"version": 8, "bars": 4}));
+    assert_eq!(Signal::try_extract_version(&version).unwrap(), "8");
+
+    // Derived enums know the version of every variant
+    let version = UserVersion::Version1(UserV1 {
+        name: "Kai".to_string(),
+    });
+    assert_eq!(User::try_extract_version(&version).unwrap(), "1");
+}
+
+#[test]
+fn test_try_extract_version_missing_tag() {
+    let version = SignalVersion::Unknown(serde_json::json!({"bars": 4}));
+    let error = Signal::try_extract_version(&version).unwrap_err();
+    assert!(error.is_deserialize());
+    assert!(error.to_string().contains("missing field `version`"));
+}
+
+// Downgrade tests
+impl serde_versioned::IntoVersion<Task> for TaskV1 {
+    fn downgrade(current: &Task) -> Self {
+        let priority = match current.priority {
+            Priority::Low => "low",
+            Priority::High => "high",
+        };
+        TaskV1 {
+            title: current.title.clone(),
+            priority: priority.to_string(),
+        }
+    }
+}
+
+#[test]
+fn test_to_version_n() {
+    let task = Task {
+        title: "Ship".to_string(),
+        priority: Priority::High,
+    };
+
+    let v1 = task.to_version_n("1").unwrap();
+    assert_eq!(
+        serde_json::to_string(&v1).unwrap(),
+        r#"{"version":"1","title":"Ship","priority":"high"}"#
+    );
+    assert_eq!(Task::from_version(v1).unwrap(), task);
+
+    let v2 = task.to_version_n("2").unwrap();
+    assert_eq!(Task::version_tag(&v2), "2");
+
+    let Err(error) = task.to_version_n("3") else {
+        panic!("version 3 is not declared");
+    };
+    assert_eq!(error.version, "3");
+
+    // Without downgrades, only the latest version can be written
+    let user = User {
+        name: "Lea".to_string(),
+        age: 8,
+    };
+    assert!(user.to_version_n("2").is_ok());
+    assert!(user.to_version_n("1").is_err());
+
+    assert_eq!(Task::version_count_reachable_from("1"), 2);
+    assert_eq!(Task::version_count_reachable_from("2"), 2);
+}
+
+// Explicit version number tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [ReleaseV1 = "1.0", ReleaseV2 = "2.0"])]
+struct Release {
+    pub name: String,
+    pub draft: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReleaseV1 {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReleaseV2 {
+    pub name: String,
+    pub draft: bool,
+}
+
+impl serde_versioned::FromVersion<Release> for ReleaseV1 {
+    fn convert(self) -> Release {
+        Release {
+            name: self.name,
+            draft: false,
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Release> for ReleaseV2 {
+    fn convert(self) -> Release {
+        Release {
+            name: self.name,
+            draft: self.draft,
+        }
+    }
+}
+
+#[test]
+fn test_explicit_version_numbers() {
+    let release = Release {
+        name: "beta".to_string(),
+        draft: true,
+    };
+    let json = release.to_format(serde_json::to_string).unwrap();
+    assert_eq!(json, r#"{"version":"2.0","name":"beta","draft":true}"#);
+
+    let restored =
+        Release::from_format(r#"{"version":"1.0","name":"beta"}"#, serde_json::from_str).unwrap();
+    assert!(!restored.draft);
+
+    // Variants are still named by position
+    let version = ReleaseVersion::Version1(ReleaseV1 {
+        name: "beta".to_string(),
+    });
+    assert_eq!(Release::version_tag(&version), "1.0");
+    assert_eq!(Release::upgrade_path("1.0"), vec!["1.0", "2.0"]);
+    assert!(
+        Release::from_format(r#"{"version":"1","name":"beta"}"#, serde_json::from_str).is_err()
+    );
+}
+
+// Chained migration tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [PointV1, PointV2, PointV3], chained)]
+struct Point {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PointV1 {
+    pub x: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PointV2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PointV3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl serde_versioned::FromVersion<PointV2> for PointV1 {
+    fn convert(self) -> PointV2 {
+        PointV2 { x: self.x, y: 0.0 }
+    }
+}
+
+impl serde_versioned::TryFromVersion<PointV3> for PointV2 {
+    fn try_convert(self) -> Result<PointV3, serde_versioned::VersionConversionError> {
+        if self.y.is_nan() {
+            return Err(serde_versioned::VersionConversionError::new(
+                "2",
+                "y is not a number".into(),
+            ));
+        }
+        Ok(PointV3 {
+            x: self.x,
+            y: self.y,
+            z: 0.0,
+        })
+    }
+}
+
+impl serde_versioned::FromVersion<Point> for PointV3 {
+    fn convert(self) -> Point {
+        Point {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+        }
+    }
+}
+
+#[test]
+fn test_chained() {
+    let point = Point::from_format(r#"{"version":"1","x":1.5}"#, serde_json::from_str).unwrap();
+    assert_eq!(
+        point,
+        Point {
+            x: 1.5,
+            y: 0.0,
+            z: 0.0
+        }
+    );
+
+    let point =
+        Point::from_format(r#"{"version":"2","x":1.5,"y":2.5}"#, serde_json::from_str).unwrap();
+    assert_eq!(point.y, 2.5);
+
+    let point = Point::from_legacy_v1(r#"{"x":4.0}"#, serde_json::from_str).unwrap();
+    assert_eq!(point.x, 4.0);
+}
+
+#[test]
+fn test_chained_step_error() {
+    let version = PointVersion::Version2(PointV2 {
+        x: 1.0,
+        y: f64::NAN,
+    });
+    let error = Point::from_version(version).unwrap_err();
+    assert_eq!(error.version, "2");
+    assert_eq!(error.context.as_deref(), Some("PointV2 → PointV3"));
+}
+
+// Latest version construction tests
+#[test]
+fn test_from_latest() {
+    let user = User::from_latest(UserV2 {
+        name: "Mio".to_string(),
+        age: 27,
+    });
+    assert_eq!(
+        user,
+        User {
+            name: "Mio".to_string(),
+            age: 27
+        }
+    );
+
+    let version = user.to_version();
+    assert_eq!(User::from_latest(User::into_latest(version).unwrap()), user);
+}
+
+// Version peeking tests
+#[test]
+fn test_peek_version() {
+    let version =
+        User::peek_version(r#"{"name":"Nao","version":"1"}"#, serde_json::from_str).unwrap();
+    assert_eq!(version, "1");
+
+    // Malformed fields are not read
+    let version = User::peek_version(
+        r#"{"version":"2","name":42,"age":"old"}"#,
+        serde_json::from_str,
+    )
+    .unwrap();
+    assert_eq!(version, "2");
+    assert!(
+        User::from_format(
+            r#"{"version":"2","name":42,"age":"old"}"#,
+            serde_json::from_str
+        )
+        .is_err()
+    );
+
+    let error = User::peek_version(r#"{"name":"Nao"}"#, serde_json::from_str).unwrap_err();
+    assert!(error.is_deserialize());
+}
+
+// Accepted version range tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [SessionV1, SessionV2], accept = "2..", async)]
+struct Session {
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionV1 {
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionV2 {
+    pub token: String,
+}
+
+impl serde_versioned::FromVersion<Session> for SessionV1 {
+    fn convert(self) -> Session {
+        Session { token: self.token }
+    }
+}
+
+impl serde_versioned::FromVersion<Session> for SessionV2 {
+    fn convert(self) -> Session {
+        Session { token: self.token }
+    }
+}
+
+// Rejected versions are never converted, so only the accepted one needs an async conversion
+impl serde_versioned::AsyncFromVersion<Session> for SessionV2 {
+    async fn convert_async(self) -> Result<Session, serde_versioned::VersionConversionError> {
+        Ok(serde_versioned::FromVersion::convert(self))
+    }
+}
+
+#[test]
+fn test_accept_range() {
+    let session =
+        Session::from_format(r#"{"version":"2","token":"abc"}"#, serde_json::from_str).unwrap();
+    assert_eq!(session.token, "abc");
+
+    let error =
+        Session::from_format(r#"{"version":"1","token":"abc"}"#, serde_json::from_str).unwrap_err();
+    assert!(error.is_version_conversion());
+    assert!(
+        error
+            .to_string()
+            .contains("version is outside the accepted range `2..`")
+    );
+}
+
+#[test]
+fn test_accept_range_async() {
+    let version: SessionVersion = serde_json::from_str(r#"{"version":"2","token":"abc"}"#).unwrap();
+    assert_eq!(
+        block_on(Session::from_version_async(version))
+            .unwrap()
+            .token,
+        "abc"
+    );
+
+    let version: SessionVersion = serde_json::from_str(r#"{"version":"1","token":"abc"}"#).unwrap();
+    let error = block_on(Session::from_version_async(version)).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("version is outside the accepted range `2..`")
+    );
+}
+
+// Tuple struct tests
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Limits {
+    pub max_connections: u32,
+    pub timeout_secs: u64,
+}
+
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [ConfigV1, ConfigV2], identity)]
+struct Config(Limits);
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConfigV1 {
+    pub max_connections: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConfigV2(Limits);
+
+impl serde_versioned::FromVersion<Config> for ConfigV1 {
+    fn convert(self) -> Config {
+        Config(Limits {
+            max_connections: self.max_connections,
+            timeout_secs: 30,
+        })
+    }
+}
+
+#[test]
+fn test_tuple_struct() {
+    let config = Config::from_format(
+        r#"{"version":"1","max_connections":8}"#,
+        serde_json::from_str,
+    )
+    .unwrap();
+    let Config(limits) = &config;
+    assert_eq!(limits.timeout_secs, 30);
+
+    let json = config.to_format(serde_json::to_string).unwrap();
+    assert_eq!(
+        json,
+        r#"{"version":"2","max_connections":8,"timeout_secs":30}"#
+    );
+    assert_eq!(
+        Config::from_format(&json, serde_json::from_str).unwrap(),
+        config
+    );
+    assert_eq!(
+        Config::from_version(config.clone().into_version()).unwrap(),
+        config
+    );
+}
+
+// Migration report tests
+#[test]
+fn test_migrate_with_report() {
+    let (output, changes) = User::migrate_with_report(
+        r#"{"version":"1","name":"Oto"}"#,
+        serde_json::from_str,
+        serde_json::to_string,
+    )
+    .unwrap();
+    assert_eq!(output, r#"{"version":"2","name":"Oto","age":0}"#);
+    assert_eq!(
+        changes,
+        vec![
+            serde_versioned::FieldChange {
+                field: "age".to_string(),
+                old: None,
+                new: Some(serde_json::json!(0)),
+            },
+            serde_versioned::FieldChange {
+                field: "version".to_string(),
+                old: Some(serde_json::json!("1")),
+                new: Some(serde_json::json!("2")),
+            },
+        ]
+    );
+
+    // Nothing changes for the latest version
+    let (_, changes) = User::migrate_with_report(
+        r#"{"version":"2","name":"Oto","age":3}"#,
+        serde_json::from_str,
+        serde_json::to_string,
+    )
+    .unwrap();
+    assert!(changes.is_empty());
+}
+
+// Custom tag name tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [ScheduleV1, ScheduleV2], tag = "_schema")]
+struct Schedule {
+    pub cron: String,
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScheduleV1 {
+    pub cron: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScheduleV2 {
+    pub cron: String,
+    pub enabled: bool,
+}
+
+impl serde_versioned::FromVersion<Schedule> for ScheduleV1 {
+    fn convert(self) -> Schedule {
+        Schedule {
+            cron: self.cron,
+            enabled: true,
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Schedule> for ScheduleV2 {
+    fn convert(self) -> Schedule {
+        Schedule {
+            cron: self.cron,
+            enabled: self.enabled,
+        }
+    }
+}
+
+#[test]
+fn test_custom_tag() {
+    assert_eq!(Schedule::VERSION_TAG, "_schema");
+    assert_eq!(User::VERSION_TAG, "version");
+
+    let schedule = Schedule {
+        cron: "0 * * * *".to_string(),
+        enabled: false,
+    };
+    let json = schedule.to_format(serde_json::to_string).unwrap();
+    assert_eq!(
+        json,
+        r#"{"_schema":"2","cron":"0 * * * *","enabled":false}"#
+    );
+
+    let restored =
+        Schedule::from_format(r#"{"_schema":"1","cron":"@daily"}"#, serde_json::from_str).unwrap();
+    assert!(restored.enabled);
+
+    // The probe and the loosely-typed value use the same key
+    assert_eq!(
+        Schedule::peek_version(&json, serde_json::from_str).unwrap(),
+        "2"
+    );
+    assert!(Schedule::peek_version(r#"{"version":"2"}"#, serde_json::from_str).is_err());
+
+    let stored = serde_versioned::VersionedValue::from_typed(&schedule).unwrap();
+    assert_eq!(stored.version, "2");
+    assert!(stored.value.get("_schema").is_none());
+    assert_eq!(stored.into_typed::<Schedule>().unwrap(), schedule);
+}
+
+// Reader and writer tests
+#[test]
+fn test_from_reader_to_writer() {
+    let input = std::io::Cursor::new(br#"{"version":"1","name":"Pia"}"#);
+    let user = User::from_reader(input, serde_json::from_reader).unwrap();
+    assert_eq!(user.name, "Pia");
+    assert_eq!(user.age, 0);
+
+    let mut output = Vec::new();
+    user.to_writer(&mut output, serde_json::to_writer).unwrap();
+    assert_eq!(output, br#"{"version":"2","name":"Pia","age":0}"#);
+
+    let input = std::io::Cursor::new(b"{\"version\":".to_vec());
+    let error = User::from_reader(input, serde_json::from_reader).unwrap_err();
+    assert!(error.is_deserialize());
+    if let serde_versioned::FormatError::Deserialize { input, .. } = error {
+        assert!(input.is_none());
+    }
+}
+
+// One-directional enum tests
+#[derive(Versioned, Debug, PartialEq, Clone)]
+#[versioned(versions = [AuditEntryV1], identity, derive_serde = "serialize")]
+struct AuditEntry {
+    pub action: String,
+}
+
+// Only written, so the version struct needs no `Deserialize`
+#[derive(Serialize, Clone)]
+pub struct AuditEntryV1 {
+    pub action: String,
+}
+
+#[derive(Versioned, Debug, PartialEq, Clone)]
+#[versioned(versions = [ReadingV1, ReadingV2], derive_serde = "deserialize")]
+struct Reading {
+    pub celsius: f64,
+}
+
+// Only read, so the version structs need no `Serialize`
+#[derive(Deserialize, Clone)]
+pub struct ReadingV1 {
+    pub fahrenheit: f64,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ReadingV2 {
+    pub celsius: f64,
+}
+
+impl serde_versioned::FromVersion<Reading> for ReadingV1 {
+    fn convert(self) -> Reading {
+        Reading {
+            celsius: (self.fahrenheit - 32.0) * 5.0 / 9.0,
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Reading> for ReadingV2 {
+    fn convert(self) -> Reading {
+        Reading {
+            celsius: self.celsius,
+        }
+    }
+}
+
+#[test]
+fn test_derive_serde_one_direction() {
+    let entry = AuditEntry {
+        action: "login".to_string(),
+    };
+    assert_eq!(
+        entry.to_format(serde_json::to_string).unwrap(),
+        r#"{"version":"1","action":"login"}"#
+    );
+
+    let reading = Reading::from_format(
+        r#"{"version":"1","fahrenheit":212.0}"#,
+        serde_json::from_str,
+    )
+    .unwrap();
+    assert_eq!(reading.celsius, 100.0);
+    let reading = Reading::from_body_and_version(
+        "2",
+        &mut serde_json::Deserializer::from_str(r#"{"celsius":21.5}"#),
+    )
+    .unwrap();
+    assert_eq!(reading.celsius, 21.5);
+}
+
+// JSON array reader tests
+#[test]
+fn test_from_json_array_reader() {
+    let input = std::io::Cursor::new(
+        br#" [ {"version":"1","name":"Ada"},
+              {"version":"2","name":"Bo","age":41} ] "#,
+    );
+    let users: Vec<User> = User::from_json_array_reader(input)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        users,
+        vec![
+            User {
+                name: "Ada".to_string(),
+                age: 0,
+            },
+            User {
+                name: "Bo".to_string(),
+                age: 41,
+            },
+        ]
+    );
+
+    let empty = std::io::Cursor::new(b"[]");
+    assert_eq!(User::from_json_array_reader(empty).count(), 0);
+
+    // Malformed JSON ends the iteration after the error
+    let truncated = std::io::Cursor::new(br#"[{"version":"1","name":"Ada"},{"version""#);
+    let results: Vec<_> = User::from_json_array_reader(truncated).collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(results[1].as_ref().unwrap_err().is_deserialize());
+
+    let not_array = std::io::Cursor::new(br#"{"version":"1","name":"Ada"}"#);
+    let results: Vec<_> = User::from_json_array_reader(not_array).collect();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_err());
+}
+
+// Binary format tests
+#[test]
+fn test_from_bytes_to_bytes() {
+    use serde_versioned::{FormatError, FormatInput};
+
+    let user = User {
+        name: "Quinn".to_string(),
+        age: 52,
+    };
+    let bytes = user.to_bytes(rmp_serde::to_vec).unwrap();
+    assert_eq!(
+        User::from_bytes(&bytes, rmp_serde::from_slice).unwrap(),
+        user
+    );
+
+    let v1 = rmp_serde::to_vec_named(&UserVersion::Version1(UserV1 {
+        name: "Rey".to_string(),
+    }))
+    .unwrap();
+    let restored = User::from_bytes(&v1, rmp_serde::from_slice).unwrap();
+    assert_eq!(restored.name, "Rey");
+    assert_eq!(restored.age, 0);
+
+    // Binary input is summarized by its length
+    let error = User::from_bytes(&bytes[..3], rmp_serde::from_slice).unwrap_err();
+    let FormatError::Deserialize { input, .. } = &error else {
+        panic!("Expected Deserialize error");
+    };
+    assert_eq!(input, &Some(FormatInput::Bytes { len: 3 }));
+    assert!(error.to_string().ends_with("(input: 3 bytes)"));
+}
+
+// Custom version order tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(
+    versions = [ForecastV1 = "2023.11", ForecastV2 = "2024.06"],
+    identity,
+    version_parser = "compare_release_dates",
+)]
+struct Forecast {
+    pub city: String,
+    pub celsius: f64,
+}
+
+/// Orders `YYYY.MM` release dates.
+fn compare_release_dates(a: &str, b: &str) -> std::cmp::Ordering {
+    let parse = |date: &str| {
+        let (year, month) = date.split_once('.').unwrap_or((date, "0"));
+        (
+            year.parse::<u32>().unwrap_or(0),
+            month.parse::<u32>().unwrap_or(0),
+        )
+    };
+    parse(a).cmp(&parse(b))
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ForecastV1 {
+    pub city: String,
+    pub fahrenheit: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ForecastV2 {
+    pub city: String,
+    pub celsius: f64,
+}
+
+impl serde_versioned::FromVersion<Forecast> for ForecastV1 {
+    fn convert(self) -> Forecast {
+        Forecast {
+            city: self.city,
+            celsius: (self.fahrenheit - 32.0) * 5.0 / 9.0,
+        }
+    }
+}
+
+#[test]
+fn test_version_parser() {
+    use std::cmp::Ordering;
+
+    assert_eq!(
+        Forecast::compare_versions("2023.11", "2024.06"),
+        Some(Ordering::Less)
+    );
+    // Versions from newer releases are still ordered
+    assert_eq!(
+        Forecast::compare_versions("2025.01", Forecast::LATEST_VERSION),
+        Some(Ordering::Greater)
+    );
+
+    let forecast = Forecast::from_format(
+        r#"{"version":"2023.11","city":"Oslo","fahrenheit":50.0}"#,
+        serde_json::from_str,
+    )
+    .unwrap();
+    assert!((forecast.celsius - 10.0).abs() < 1e-9);
+
+    // Without a parser, versions are ordered as listed
+    assert_eq!(User::compare_versions("1", "2"), Some(Ordering::Less));
+    assert_eq!(User::compare_versions("2", "2"), Some(Ordering::Equal));
+    assert_eq!(User::compare_versions("1", "9"), None);
+}
+
+// Forwarded derive tests
+#[derive(Versioned, Debug, PartialEq, Clone)]
+#[versioned(
+    versions = [CouponV1, CouponV2],
+    derive(Debug, PartialEq, Clone, serde::Serialize),
+)]
+struct Coupon {
+    pub code: String,
+    pub percent: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CouponV1 {
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CouponV2 {
+    pub code: String,
+    pub percent: u8,
+}
+
+impl serde_versioned::FromVersion<Coupon> for CouponV1 {
+    fn convert(self) -> Coupon {
+        Coupon {
+            code: self.code,
+            percent: 10,
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Coupon> for CouponV2 {
+    fn convert(self) -> Coupon {
+        Coupon {
+            code: self.code,
+            percent: self.percent,
+        }
+    }
+}
+
+#[test]
+fn test_forwarded_derives() {
+    let coupon = Coupon {
+        code: "SPRING".to_string(),
+        percent: 15,
+    };
+    let version: CouponVersion =
+        serde_json::from_str(r#"{"version":"2","code":"SPRING","percent":15}"#).unwrap();
+    assert_eq!(version, coupon.to_version());
+    assert_ne!(
+        version,
+        CouponVersion::Version1(CouponV1 {
+            code: "SPRING".to_string(),
+        })
+    );
+    assert!(format!("{version:?}").contains("SPRING"));
+}
+
+// Adjacent representation tests
+#[derive(Versioned, Debug, PartialEq, Clone)]
+#[versioned(versions = [LabelV1, LabelV2], repr = "adjacent", content = "data")]
+struct Label {
+    pub text: String,
+    pub color: String,
+}
+
+/// The first version is only the text, which the internal representation cannot tag.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LabelV1(pub String);
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LabelV2 {
+    pub text: String,
+    pub color: String,
+}
+
+impl serde_versioned::FromVersion<Label> for LabelV1 {
+    fn convert(self) -> Label {
+        Label {
+            text: self.0,
+            color: "gray".to_string(),
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Label> for LabelV2 {
+    fn convert(self) -> Label {
+        Label {
+            text: self.text,
+            color: self.color,
+        }
+    }
+}
+
+#[test]
+fn test_adjacent_repr() {
+    use serde_versioned::VersionedValue;
+
+    let label = Label {
+        text: "urgent".to_string(),
+        color: "red".to_string(),
+    };
+    let json = label.to_format(serde_json::to_string).unwrap();
+    assert_eq!(
+        json,
+        r#"{"version":"2","data":{"text":"urgent","color":"red"}}"#
+    );
+    assert_eq!(
+        Label::from_format(&json, serde_json::from_str).unwrap(),
+        label
+    );
+
+    let label =
+        Label::from_format(r#"{"version":"1","data":"todo"}"#, serde_json::from_str).unwrap();
+    assert_eq!(label.color, "gray");
+    assert_eq!(
+        Label::peek_version(r#"{"version":"1","data":"todo"}"#, serde_json::from_str).unwrap(),
+        "1"
+    );
+
+    // Stored values hold the content without the tag
+    let stored = VersionedValue::new("1", serde_json::json!("todo"));
+    assert_eq!(stored.into_typed::<Label>().unwrap().text, "todo");
+    let stored = VersionedValue::from_typed(&label).unwrap();
+    assert_eq!(
+        stored.value,
+        serde_json::json!({ "text": "todo", "color": "gray" })
+    );
+    assert_eq!(stored.into_typed::<Label>().unwrap(), label);
+}
+
+// Integer tag tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [GaugeV1, GaugeV2], tag_type = "int", tag = "schema")]
+struct Gauge {
+    pub name: String,
+    pub unit: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GaugeV1 {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GaugeV2 {
+    pub name: String,
+    pub unit: String,
+}
+
+impl serde_versioned::FromVersion<Gauge> for GaugeV1 {
+    fn convert(self) -> Gauge {
+        Gauge {
+            name: self.name,
+            unit: "count".to_string(),
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Gauge> for GaugeV2 {
+    fn convert(self) -> Gauge {
+        Gauge {
+            name: self.name,
+            unit: self.unit,
+        }
+    }
+}
+
+#[test]
+fn test_int_tags() {
+    let gauge = Gauge {
+        name: "latency".to_string(),
+        unit: "ms".to_string(),
+    };
+    let json = gauge.to_json().unwrap();
+    assert_eq!(json, r#"{"schema":2,"name":"latency","unit":"ms"}"#);
+    assert_eq!(Gauge::from_json(&json).unwrap(), gauge);
+    assert_eq!(Gauge::extract_version_string(&gauge.to_version()), "2");
+    assert_eq!(Gauge::tag_value(&gauge.to_version()), serde_json::json!(2));
+
+    let old = Gauge::from_yaml("schema: 1\nname: requests\n").unwrap();
+    assert_eq!(old.unit, "count");
+    // A string of digits is read as well
+    assert_eq!(
+        Gauge::from_json(r#"{"schema":"1","name":"requests"}"#).unwrap(),
+        old
+    );
+    assert_eq!(
+        Gauge::peek_version(r#"{"schema":1,"name":"requests"}"#, serde_json::from_str).unwrap(),
+        "1"
+    );
+
+    let error =
+        Gauge::from_format(r#"{"schema":3,"name":"requests"}"#, serde_json::from_str).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("unknown version `3`, expected one of `1`, `2`")
+    );
+    let error = Gauge::from_json(r#"{"schema":2,"name":"requests"}"#).unwrap_err();
+    assert!(error.to_string().contains("missing field `unit`"));
+
+    let stored = serde_versioned::VersionedValue::from_typed(&gauge).unwrap();
+    assert_eq!(stored.version, "2");
+    assert_eq!(stored.into_typed::<Gauge>().unwrap(), gauge);
+}
+
+// Unknown version fallback tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [BeaconV1, BeaconV2], unknown_as = "2")]
+struct Beacon {
+    pub id: String,
+    pub interval_secs: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BeaconV1 {
+    pub id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BeaconV2 {
+    pub id: String,
+    pub interval_secs: u32,
+}
+
+impl serde_versioned::FromVersion<Beacon> for BeaconV1 {
+    fn convert(self) -> Beacon {
+        Beacon {
+            id: self.id,
+            interval_secs: 60,
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Beacon> for BeaconV2 {
+    fn convert(self) -> Beacon {
+        Beacon {
+            id: self.id,
+            interval_secs: self.interval_secs,
+        }
+    }
+}
+
+#[test]
+fn test_unknown_as() {
+    let beacon = Beacon {
+        id: "b1".to_string(),
+        interval_secs: 30,
+    };
+    let json = beacon.to_json().unwrap();
+    assert_eq!(json, r#"{"version":"2","id":"b1","interval_secs":30}"#);
+    assert_eq!(Beacon::from_json(&json).unwrap(), beacon);
+    assert_eq!(
+        Beacon::from_json(r#"{"version":"1","id":"b1"}"#)
+            .unwrap()
+            .interval_secs,
+        60
+    );
+
+    // A newer version is read as version 2, dropping the fields it does not know
+    let newer = r#"{"version":"3","id":"b1","interval_secs":30,"jitter":5}"#;
+    assert_eq!(Beacon::from_json(newer).unwrap(), beacon);
+
+    // Version 1 is not a fallback, so its own errors are reported as they are
+    let error = Beacon::from_json(r#"{"version":"1"}"#).unwrap_err();
+    assert!(error.to_string().contains("missing field `id`"));
+
+    let error = Beacon::from_json(r#"{"version":"3","id":"b1"}"#).unwrap_err();
+    assert!(error.to_string().contains(
+        "unknown version `3`, expected one of `1`, `2`; reading it as version 2 failed: missing field `interval_secs`"
+    ));
+}
+
+// Validation hook tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [MemberV1, MemberV2], validate = "Member::validate", async)]
+struct Member {
+    pub name: String,
+    pub age: u32,
+}
+
+impl Member {
+    fn validate(&self) -> Result<(), serde_versioned::VersionConversionError> {
+        if self.name.is_empty() {
+            return Err(serde_versioned::VersionConversionError::with_context(
+                Self::LATEST_VERSION,
+                "name is empty".into(),
+                "validation",
+            ));
+        }
+        if self.age >= 150 {
+            return Err(serde_versioned::VersionConversionError::with_context(
+                Self::LATEST_VERSION,
+                format!("age {} is out of range", self.age).into(),
+                "validation",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MemberV1 {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MemberV2 {
+    pub name: String,
+    pub age: u32,
+}
+
+impl serde_versioned::FromVersion<Member> for MemberV1 {
+    fn convert(self) -> Member {
+        Member {
+            name: self.name,
+            age: 0,
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Member> for MemberV2 {
+    fn convert(self) -> Member {
+        Member {
+            name: self.name,
+            age: self.age,
+        }
+    }
+}
+
+impl serde_versioned::AsyncFromVersion<Member> for MemberV1 {
+    async fn convert_async(self) -> Result<Member, serde_versioned::VersionConversionError> {
+        Ok(serde_versioned::FromVersion::convert(self))
+    }
+}
+
+impl serde_versioned::AsyncFromVersion<Member> for MemberV2 {
+    async fn convert_async(self) -> Result<Member, serde_versioned::VersionConversionError> {
+        Ok(serde_versioned::FromVersion::convert(self))
+    }
+}
+
+#[test]
+fn test_validate_hook() {
+    let member = Member::from_format(
+        r#"{"version":"2","name":"Zed","age":40}"#,
+        serde_json::from_str,
+    )
+    .unwrap();
+    assert_eq!(member.age, 40);
+
+    // Invalid values are rejected whichever version they were read from
+    let error =
+        Member::from_format(r#"{"version":"1","name":""}"#, serde_json::from_str).unwrap_err();
+    assert!(error.is_version_conversion());
+    assert!(error.to_string().contains("name is empty"));
+
+    let error = Member::from_format(
+        r#"{"version":"2","name":"Old","age":200}"#,
+        serde_json::from_str,
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("age 200 is out of range"));
+}
+
+#[test]
+fn test_validate_hook_async() {
+    let version: MemberVersion = serde_json::from_str(r#"{"version":"1","name":""}"#).unwrap();
+    let error = block_on(Member::from_version_async(version)).unwrap_err();
+    assert!(error.to_string().contains("name is empty"));
+    assert_eq!(error.version_index(), Some(1));
+
+    let version: MemberVersion =
+        serde_json::from_str(r#"{"version":"2","name":"Zed","age":40}"#).unwrap();
+    assert_eq!(
+        block_on(Member::from_version_async(version)).unwrap().age,
+        40
+    );
+}
+
+// Custom latest version tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [BasketV1, BasketV2], to_latest = "Basket::to_latest")]
+struct Basket {
+    pub lines: Vec<u32>,
+}
+
+impl Basket {
+    fn to_latest(&self) -> BasketVersion {
+        BasketVersion::Version2(BasketV2 {
+            lines: self.lines.clone(),
+            total: self.lines.iter().sum(),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BasketV1 {
+    pub lines: Vec<u32>,
+}
+
+/// Stores the total for readers that do not add up the lines.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BasketV2 {
+    pub lines: Vec<u32>,
+    pub total: u32,
+}
+
+impl serde_versioned::FromVersion<Basket> for BasketV1 {
+    fn convert(self) -> Basket {
+        Basket { lines: self.lines }
+    }
+}
+
+impl serde_versioned::FromVersion<Basket> for BasketV2 {
+    fn convert(self) -> Basket {
+        Basket { lines: self.lines }
+    }
+}
+
+#[test]
+fn test_to_latest() {
+    let basket = Basket {
+        lines: vec![3, 4, 5],
+    };
+    let json = basket.to_json().unwrap();
+    assert_eq!(json, r#"{"version":"2","lines":[3,4,5],"total":12}"#);
+    assert_eq!(
+        serde_json::to_string(&basket.clone().into_version()).unwrap(),
+        json
+    );
+    assert_eq!(Basket::from_json(&json).unwrap(), basket);
+}
+
+// Current version tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [LampV1, LampV2], current = LampV1, identity)]
+struct Lamp {
+    pub on: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LampV1 {
+    pub on: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LampV2 {
+    pub on: bool,
+    pub brightness: u8,
+}
+
+impl serde_versioned::FromVersion<Lamp> for LampV2 {
+    fn convert(self) -> Lamp {
+        Lamp { on: self.on }
+    }
+}
+
+#[test]
+fn test_current_version() {
+    let lamp = Lamp { on: true };
+    let json = lamp.to_json().unwrap();
+    assert_eq!(json, r#"{"version":"1","on":true}"#);
+    assert_eq!(Lamp::LATEST_VERSION, "1");
+    assert_eq!(Lamp::all_versions(), ["1", "2"]);
+
+    // The unreleased version is read already
+    assert_eq!(
+        Lamp::from_json(r#"{"version":"2","on":true,"brightness":80}"#).unwrap(),
+        lamp
+    );
+    assert!(lamp.to_version_n("2").is_err());
+}
+
+// Version group tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [SurveyV1, SurveyV2], group = "survey_schema", identity)]
+struct Survey {
+    pub title: String,
+}
+
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [SurveyAnswersV1, SurveyAnswersV2], group = "survey_schema", identity)]
+struct SurveyAnswers {
+    pub answers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SurveyV1 {
+    pub title: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SurveyV2 {
+    pub title: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SurveyAnswersV1 {
+    pub answers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SurveyAnswersV2 {
+    pub answers: Vec<String>,
+}
+
+impl serde_versioned::FromVersion<Survey> for SurveyV1 {
+    fn convert(self) -> Survey {
+        Survey { title: self.title }
+    }
+}
+
+impl serde_versioned::FromVersion<SurveyAnswers> for SurveyAnswersV1 {
+    fn convert(self) -> SurveyAnswers {
+        SurveyAnswers {
+            answers: self.answers,
+        }
+    }
+}
+
+#[test]
+fn test_version_group() {
+    assert_eq!(Survey::group_name(), "survey_schema");
+    assert_eq!(SurveyAnswers::VERSION_GROUP, Some("survey_schema"));
+    assert_eq!(User::VERSION_GROUP, None);
+
+    let survey: SurveyVersion = serde_json::from_str(r#"{"version":"2","title":"Q3"}"#).unwrap();
+    let answers: SurveyAnswersVersion =
+        serde_json::from_str(r#"{"version":"2","answers":["yes"]}"#).unwrap();
+    Survey::assert_same_version::<SurveyAnswers>(&survey, &answers).unwrap();
+
+    let stale: SurveyAnswersVersion =
+        serde_json::from_str(r#"{"version":"1","answers":["yes"]}"#).unwrap();
+    let error = Survey::assert_same_version::<SurveyAnswers>(&survey, &stale).unwrap_err();
+    assert_eq!(error.version, "2");
+    assert!(
+        error
+            .source
+            .to_string()
+            .contains("does not match version 1")
+    );
+
+    // Structs outside the group are not compared
+    let user = User {
+        name: "Kai".to_string(),
+        age: 9,
+    }
+    .to_version();
+    let error = Survey::assert_same_version::<User>(&survey, &user).unwrap_err();
+    assert!(
+        error
+            .source
+            .to_string()
+            .contains("not in the same version group")
+    );
+}
+
+// Field option tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [PatientV1, PatientV2], identity)]
+struct Patient {
+    pub name: String,
+    #[versioned(rename_to = "age_years")]
+    pub age: u32,
+    #[serde(skip)]
+    #[versioned(skip)]
+    pub cached_summary: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PatientV1 {
+    pub name: String,
+    pub age: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PatientV2 {
+    pub name: String,
+    pub age_years: u32,
+}
+
+impl serde_versioned::FromVersion<Patient> for PatientV1 {
+    fn convert(self) -> Patient {
+        Patient {
+            name: self.name,
+            age: self.age,
+            cached_summary: None,
+        }
+    }
+}
+
+#[test]
+fn test_rename_to() {
+    let patient = Patient {
+        name: "Ines".to_string(),
+        age: 40,
+        cached_summary: None,
+    };
+    let json = patient.to_json().unwrap();
+    assert_eq!(json, r#"{"version":"2","name":"Ines","age_years":40}"#);
+    assert_eq!(
+        serde_json::to_string(&patient.clone().into_version()).unwrap(),
+        json
+    );
+    assert_eq!(Patient::from_json(&json).unwrap(), patient);
+    assert_eq!(
+        Patient::from_json(r#"{"version":"1","name":"Ines","age":40}"#).unwrap(),
+        patient
+    );
+}
+
+#[test]
+fn test_skipped_field() {
+    let patient = Patient {
+        name: "Ines".to_string(),
+        age: 40,
+        cached_summary: Some("Ines, 40".to_string()),
+    };
+    let json = patient.to_json().unwrap();
+    assert_eq!(json, r#"{"version":"2","name":"Ines","age_years":40}"#);
+
+    // The generated identity conversion leaves skipped fields at their default
+    assert_eq!(Patient::from_json(&json).unwrap().cached_summary, None);
+}
+
+// Format-preserving migration tests
+#[test]
+fn test_migrate_same_format() {
+    use serde_versioned::Format;
+
+    let json = r#"{"version":"1","name":"Ava"}"#;
+    assert_eq!(Format::sniff(json), Format::Json);
+    assert_eq!(
+        User::migrate_same_format(json).unwrap(),
+        r#"{"version":"2","name":"Ava","age":0}"#
+    );
+
+    let yaml = "version: '1'\nname: Ava\n";
+    assert_eq!(Format::sniff(yaml), Format::Yaml);
+    assert_eq!(
+        User::migrate_same_format(yaml).unwrap(),
+        "version: '2'\nname: Ava\nage: 0\n"
+    );
+
+    let error = User::migrate_same_format("version: '1'\n").unwrap_err();
+    assert!(error.to_string().contains("yaml: "));
+}
+
+// Migration tests
+#[test]
+fn test_migrate() {
+    let output = User::migrate(
+        r#"{"version":"1","name":"Bea"}"#,
+        serde_json::from_str,
+        serde_json::to_string,
+    )
+    .unwrap();
+    assert_eq!(output, r#"{"version":"2","name":"Bea","age":0}"#);
+
+    // The latest version round-trips unchanged
+    let latest = r#"{"version":"2","name":"Bea","age":61}"#;
+    assert_eq!(
+        User::migrate(latest, serde_json::from_str, serde_json::to_string).unwrap(),
+        latest
+    );
+
+    let error = User::migrate("{", serde_json::from_str, serde_json::to_string).unwrap_err();
+    assert!(error.is_deserialize());
+}
+
+#[test]
+fn test_upgrade_between() {
+    let upgrade = |input, from, to| {
+        User::upgrade_between(input, from, to, serde_json::from_str, serde_json::to_string)
+    };
+    assert_eq!(
+        upgrade(r#"{"version":"1","name":"Bea"}"#, "1", "2").unwrap(),
+        r#"{"version":"2","name":"Bea","age":0}"#
+    );
+
+    let error = upgrade(r#"{"version":"2","name":"Bea","age":3}"#, "2", "1").unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("cannot upgrade to the older version 1")
+    );
+    let error = upgrade(r#"{"version":"1","name":"Bea"}"#, "1", "9").unwrap_err();
+    assert!(error.to_string().contains("version 9"));
+    let error = upgrade(r#"{"version":"2","name":"Bea","age":3}"#, "1", "2").unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("expected the input in version 1")
+    );
+
+    // Versions other than the latest are written through their downgrade
+    let output = Task::upgrade_between(
+        r#"{"version":"1","title":"Ship","priority":"high"}"#,
+        "1",
+        "1",
+        serde_json::from_str,
+        serde_json::to_string,
+    )
+    .unwrap();
+    assert_eq!(
+        output,
+        r#"{"version":"1","title":"Ship","priority":"high"}"#
+    );
+
+    // Input in another version is rejected before it is converted
+    let error = Task::upgrade_between(
+        r#"{"version":"1","title":"Ship","priority":"urgent"}"#,
+        "2",
+        "2",
+        serde_json::from_str,
+        serde_json::to_string,
+    )
+    .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("expected the input in version 2")
+    );
+}
+
+// Enum name tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [BadgeV1], identity, enum_name = "BadgeSchema")]
+struct Badge {
+    pub title: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BadgeV1 {
+    pub title: String,
+}
+
+// A hand-written type with the default name does not collide with the generated enum
+#[allow(dead_code)]
+struct BadgeVersion(u32);
+
+#[test]
+fn test_enum_name() {
+    let ticket = Badge {
+        title: "Reviewer".to_string(),
+    };
+    let version: BadgeSchema = ticket.to_version();
+    let _: <Badge as Versioned>::VersionEnum = version.clone();
+    assert_eq!(Badge::from_version(version).unwrap(), ticket);
+}
+
+// Existing enum tests
+#[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[versioned(versions = [TokenV1, TokenV2], enum = TokenWire, tag = "v")]
+struct Token {
+    pub secret: String,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TokenV1 {
+    pub secret: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TokenV2 {
+    pub secret: String,
+    pub scopes: Vec<String>,
+}
+
+// The wire names of the versions differ from the numbers `versions` assigns
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "v")]
+pub enum TokenWire {
+    #[serde(rename = "1", alias = "legacy")]
+    Version1(TokenV1),
+    #[serde(rename = "2")]
+    Version2(TokenV2),
+}
+
+// Conversions the derive would generate for its own enum do not conflict
+impl From<TokenV1> for TokenWire {
+    fn from(version: TokenV1) -> Self {
+        TokenWire::Version1(version)
+    }
+}
+
+impl TokenWire {
+    fn as_version_1(&self) -> Option<&TokenV1> {
+        match self {
+            TokenWire::Version1(v) => Some(v),
+            TokenWire::Version2(_) => None,
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Token> for TokenV1 {
+    fn convert(self) -> Token {
+        Token {
+            secret: self.secret,
+            scopes: Vec::new(),
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Token> for TokenV2 {
+    fn convert(self) -> Token {
+        Token {
+            secret: self.secret,
+            scopes: self.scopes,
+        }
+    }
+}
+
+#[test]
+fn test_existing_enum() {
+    let token = Token {
+        secret: "s3cr3t".to_string(),
+        scopes: vec!["read".to_string()],
+    };
+    let version: TokenWire = token.to_version();
+    let _: <Token as Versioned>::VersionEnum = version.clone();
+    assert_eq!(Token::from_version(version).unwrap(), token);
+
+    let legacy: TokenWire = serde_json::from_str(r#"{"v":"legacy","secret":"old"}"#).unwrap();
+    assert_eq!(Token::extract_version_string(&legacy), "1");
+    assert_eq!(legacy.as_version_1().unwrap().secret, "old");
+    assert_eq!(Token::from_version(legacy).unwrap().secret, "old");
+
+    let version: TokenWire = TokenV1 {
+        secret: "v1".to_string(),
+    }
+    .into();
+    assert!(Token::from_version(version).unwrap().scopes.is_empty());
+}
+
+// Local scope tests
+#[test]
+fn test_derive_in_function() {
+    #[derive(Versioned, Serialize, Deserialize, Debug, PartialEq, Clone)]
+    #[versioned(versions = [CounterV1, CounterV2], visibility = "pub(self)")]
+    struct Counter {
+        pub count: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    struct CounterV1 {
+        count: u32,
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    struct CounterV2 {
+        count: u64,
+    }
+
+    impl serde_versioned::FromVersion<Counter> for CounterV1 {
+        fn convert(self) -> Counter {
+            Counter {
+                count: self.count.into(),
+            }
+        }
+    }
+
+    impl serde_versioned::FromVersion<Counter> for CounterV2 {
+        fn convert(self) -> Counter {
+            Counter { count: self.count }
+        }
+    }
+
+    let counter =
+        Counter::from_format(r#"{"version":"1","count":3}"#, serde_json::from_str).unwrap();
+    assert_eq!(counter.count, 3);
+    let version: CounterVersion = counter.to_version();
+    assert_eq!(
+        serde_json::to_string(&version).unwrap(),
+        r#"{"version":"2","count":3}"#
+    );
+}
+
+// Generic struct tests
+#[derive(Versioned, Debug, PartialEq, Clone)]
+#[versioned(versions = [ParcelV1, ParcelV2])]
+struct Parcel<T>
+where
+    T: Serialize + serde::de::DeserializeOwned + Clone,
+{
+    pub id: u64,
+    pub payload: T,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ParcelV1<T> {
+    pub payload: T,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ParcelV2<T> {
+    pub id: u64,
+    pub payload: T,
+}
+
+impl<T> serde_versioned::FromVersion<Parcel<T>> for ParcelV1<T>
+where
+    T: Serialize + serde::de::DeserializeOwned + Clone,
+{
+    fn convert(self) -> Parcel<T> {
+        Parcel {
+            id: 0,
+            payload: self.payload,
+        }
+    }
+}
+
+impl<T> serde_versioned::FromVersion<Parcel<T>> for ParcelV2<T>
+where
+    T: Serialize + serde::de::DeserializeOwned + Clone,
+{
+    fn convert(self) -> Parcel<T> {
+        Parcel {
+            id: self.id,
+            payload: self.payload,
+        }
+    }
+}
+
+#[test]
+fn test_generic_struct() {
+    let envelope = Parcel {
+        id: 7,
+        payload: vec![1, 2, 3],
+    };
+    let json = envelope.to_format(serde_json::to_string).unwrap();
+    assert_eq!(json, r#"{"version":"2","id":7,"payload":[1,2,3]}"#);
+    assert_eq!(
+        Parcel::<Vec<i32>>::from_format(&json, serde_json::from_str).unwrap(),
+        envelope
+    );
+
+    let old: Parcel<String> =
+        Parcel::from_format(r#"{"version":"1","payload":"hi"}"#, serde_json::from_str).unwrap();
+    assert_eq!(old.id, 0);
+    assert_eq!(old.payload, "hi");
+
+    let version: ParcelVersion<String> = ParcelV1 {
+        payload: "hey".to_string(),
+    }
+    .into();
+    assert_eq!(
+        Parcel::<String>::version_for::<ParcelV1<String>>(),
+        Some("1")
+    );
+    assert!(Parcel::as_version::<ParcelV1<String>>(&version).is_some());
+}
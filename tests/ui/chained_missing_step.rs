@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Serialize, Deserialize, Clone)]
+#[versioned(versions = [UserV1, UserV2, UserV3], chained)]
+struct User {
+    pub name: String,
+    pub age: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserV1 {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserV2 {
+    pub name: String,
+    pub age: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserV3 {
+    pub name: String,
+    pub age: u32,
+}
+
+impl serde_versioned::FromVersion<UserV3> for UserV2 {
+    fn convert(self) -> UserV3 {
+        UserV3 { name: self.name, age: self.age }
+    }
+}
+
+impl serde_versioned::FromVersion<User> for UserV3 {
+    fn convert(self) -> User {
+        User { name: self.name, age: self.age }
+    }
+}
+
+fn main() {}
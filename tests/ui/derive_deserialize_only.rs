@@ -0,0 +1,19 @@
+use serde::Deserialize;
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Clone)]
+#[versioned(versions = [EventV1], identity, derive_serde = "deserialize")]
+struct Event {
+    pub name: String,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct EventV1 {
+    pub name: String,
+}
+
+fn main() {
+    // The enum is only read, so it cannot be written
+    let event = Event { name: String::new() };
+    let _ = event.to_format(serde_json::to_string);
+}
@@ -0,0 +1,18 @@
+use serde::Serialize;
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Clone)]
+#[versioned(versions = [EventV1], identity, derive_serde = "serialize")]
+struct Event {
+    pub name: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct EventV1 {
+    pub name: String,
+}
+
+fn main() {
+    // The enum is only written, so it cannot be read back
+    let _ = Event::from_format(r#"{"version":"1","name":""}"#, serde_json::from_str);
+}
@@ -0,0 +1,22 @@
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Clone)]
+#[versioned(versions = [])]
+struct User {
+    pub name: String,
+}
+
+// Every element is malformed, so no version is left
+#[derive(Versioned, Clone)]
+#[versioned(versions = [AccountV1(), "2"])]
+struct Account {
+    pub name: String,
+}
+
+#[derive(Versioned, Clone)]
+#[versioned(identity)]
+struct Profile {
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+mod api {
+    use serde::{Deserialize, Serialize};
+    use serde_versioned::Versioned;
+
+    #[derive(Versioned, Serialize, Deserialize, Clone)]
+    #[versioned(versions = [NoteV1], visibility = "pub(self)")]
+    pub struct Note {
+        pub text: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub struct NoteV1 {
+        pub text: String,
+    }
+
+    impl serde_versioned::FromVersion<Note> for NoteV1 {
+        fn convert(self) -> Note {
+            Note { text: self.text }
+        }
+    }
+}
+
+fn main() {
+    // The struct is public, but the `NoteVersion` alias is private to `api`. The enum behind it
+    // is defined as the public, doc-hidden `api::__NoteVersion`, since the public `Versioned`
+    // implementation names it
+    let _note = api::Note { text: String::new() };
+    let _version: Option<api::NoteVersion> = None;
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Serialize, Deserialize, Clone)]
+#[versioned(versions = [UserV1], enum = UserWire, enum_name = "UserSchema")]
+struct User {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct UserV1 {
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Serialize, Deserialize, Clone)]
+#[versioned(versions = [UserV1, UserV2])]
+struct User {
+    pub name: String,
+    pub age: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserV1 {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserV2 {
+    pub name: String,
+    pub age: String,
+}
+
+impl serde_versioned::FromVersion<User> for UserV1 {
+    fn convert(self) -> User {
+        User { name: self.name, age: 0 }
+    }
+}
+
+impl serde_versioned::FromVersion<User> for UserV2 {
+    fn convert(self) -> User {
+        User { name: self.name, age: 0 }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Clone)]
+#[versioned(versions = [ReadingV1 = "1", ReadingV2 = "2-beta"], tag_type = "int")]
+struct Reading {
+    pub value: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReadingV1 {
+    pub value: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReadingV2 {
+    pub value: f64,
+}
+
+fn main() {}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Clone)]
+#[versioned(versions = [UserV1, UserV2(), UserV1])]
+struct User {
+    pub name: String,
+}
+
+#[derive(Versioned, Clone)]
+#[versioned(versions = [AccountV1 = "1.0", AccountV2 = "1.0"])]
+struct Account {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserV1 {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserV2 {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccountV1 {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccountV2 {
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Serialize, Deserialize, Clone)]
+#[versioned(versions = [ContactV1, ContactV2])]
+struct Contact {
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ContactV1 {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ContactV2 {
+    pub name: String,
+    pub emial: String,
+}
+
+impl serde_versioned::FromVersion<Contact> for ContactV1 {
+    fn convert(self) -> Contact {
+        Contact {
+            name: self.name,
+            email: String::new(),
+        }
+    }
+}
+
+impl serde_versioned::FromVersion<Contact> for ContactV2 {
+    fn convert(self) -> Contact {
+        Contact {
+            name: self.name,
+            email: self.emial,
+        }
+    }
+}
+
+fn main() {}
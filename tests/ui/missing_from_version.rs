@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Serialize, Deserialize, Clone)]
+#[versioned(versions = [UserV1, UserV2])]
+struct User {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserV1 {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserV2 {
+    pub name: String,
+}
+
+impl serde_versioned::FromVersion<User> for UserV2 {
+    fn convert(self) -> User {
+        User { name: self.name }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Clone)]
+#[versioned(versions = [EventV1], identity, unknown_as = "2")]
+struct Event {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EventV1 {
+    pub name: String,
+}
+
+fn main() {}
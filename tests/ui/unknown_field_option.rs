@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Clone)]
+#[versioned(versions = [EventV1], identity)]
+struct Event {
+    #[versioned(rename = "title")]
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EventV1 {
+    pub title: String,
+}
+
+fn main() {}
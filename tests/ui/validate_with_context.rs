@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use serde_versioned::Versioned;
+
+pub struct MigrationContext;
+
+#[derive(Versioned, Serialize, Deserialize, Clone)]
+#[versioned(versions = [UserV1], context = "MigrationContext", validate = "User::validate")]
+struct User {
+    pub name: String,
+}
+
+impl User {
+    fn validate(&self) -> Result<(), serde_versioned::VersionConversionError> {
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct UserV1 {
+    pub name: String,
+}
+
+fn main() {}
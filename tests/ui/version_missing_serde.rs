@@ -0,0 +1,16 @@
+use serde::Serialize;
+use serde_versioned::Versioned;
+
+#[derive(Versioned, Clone)]
+#[versioned(versions = [EventV1], identity)]
+struct Event {
+    pub name: String,
+}
+
+// Missing `Deserialize`
+#[derive(Serialize, Clone)]
+pub struct EventV1 {
+    pub name: String,
+}
+
+fn main() {}